@@ -6,17 +6,17 @@ enum Implementation {
     Custom,
 }
 
-impl ToString for Implementation {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for Implementation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             Implementation::Ossl11 => "ossl11",
             Implementation::Ossl3 => "ossl3",
             Implementation::Custom => "custom",
-        }
-        .to_string()
+        })
     }
 }
 
+#[cfg(not(feature = "force_custom"))]
 fn read_header(lib: &pkg_config::Library, path_rel: &str) -> std::io::Result<String> {
     for dir in lib
         .include_paths
@@ -31,11 +31,18 @@ fn read_header(lib: &pkg_config::Library, path_rel: &str) -> std::io::Result<Str
         }
     }
 
-    return Err(std::io::ErrorKind::NotFound.into());
+    Err(std::io::ErrorKind::NotFound.into())
 }
 
 #[allow(unreachable_code)]
 fn main() {
+    println!(
+        "cargo:rustc-check-cfg=cfg(implementation, values(\"ossl11\", \"ossl3\", \"custom\"))"
+    );
+    println!(
+        "cargo:rustc-check-cfg=cfg(ossl3_supported, values(\"kbkdf_r\", \"argon2\", \"srtpkdf\", \"hmac_drbg\", \"kbkdf_kmac\"))"
+    );
+
     #[allow(unused_mut)]
     let mut available_implementations: Vec<Implementation> = vec![];
 
@@ -51,10 +58,23 @@ fn main() {
             }
         } else if openssl_version.starts_with("3.") {
             available_implementations.push(Implementation::Ossl3);
+            // KMAC was added to KBKDF in OpenSSL 3.1, without a new header name to detect
+            if !openssl_version.starts_with("3.0.") {
+                println!("cargo:rustc-cfg=ossl3_supported=\"kbkdf_kmac\"");
+            }
             let core_names_h = read_header(&openssl, "openssl/core_names.h").unwrap();
             if core_names_h.contains("OSSL_KDF_PARAM_KBKDF_R") {
                 println!("cargo:rustc-cfg=ossl3_supported=\"kbkdf_r\"");
             }
+            if core_names_h.contains("OSSL_KDF_NAME_ARGON2ID") {
+                println!("cargo:rustc-cfg=ossl3_supported=\"argon2\"");
+            }
+            if core_names_h.contains("OSSL_KDF_NAME_SRTPKDF") {
+                println!("cargo:rustc-cfg=ossl3_supported=\"srtpkdf\"");
+            }
+            if core_names_h.contains("OSSL_KDF_NAME_HMACDRBGKDF") {
+                println!("cargo:rustc-cfg=ossl3_supported=\"hmac_drbg\"");
+            }
         }
     }
 
@@ -73,10 +93,7 @@ fn main() {
     }
 
     for implementation in available_implementations {
-        println!(
-            "cargo:rustc-cfg=implementation=\"{}\"",
-            implementation.to_string()
-        );
+        println!("cargo:rustc-cfg=implementation=\"{}\"", implementation);
     }
     println!("cargo:rustc-link-lib=crypto");
 }
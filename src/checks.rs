@@ -0,0 +1,362 @@
+use std::convert::TryFrom;
+
+use openssl::nid::Nid;
+
+use crate::{KdfArgument, KdfCounterLocation, KdfError, KdfKbMode, KdfLUnits, KdfMacType, KdfType};
+
+// SP800-132 section 5 lower bounds, as enforced by OpenSSL 3 when "pkcs5" is not set
+const PBKDF2_MIN_KEY_BITS: u64 = 112;
+const PBKDF2_MIN_SALT_BITS: usize = 128;
+const PBKDF2_MIN_ITERATIONS: u64 = 1000;
+
+// The KMAC output lengths OpenSSL accepts in SSKDF, besides the requested output length
+const SSKDF_KMAC_MACLENS: [usize; 5] = [20, 28, 32, 48, 64];
+
+// Defaults for the counter and L widths, as used by OpenSSL
+const KBKDF_DEFAULT_R: u8 = 32;
+const KBKDF_DEFAULT_LBITS: u8 = 32;
+// The counter and L widths SP800-108 allows
+const KBKDF_VALID_WIDTHS: [u8; 4] = [8, 16, 24, 32];
+
+// RFC 3711 section 4.3.1
+const SRTP_MAX_KDR: u32 = 1 << 24;
+const SRTP_INDEX_BYTES: usize = 6;
+const SRTCP_INDEX_BYTES: usize = 4;
+
+// Defaults as used by the OpenSSL 3 scrypt implementation
+pub(crate) const SCRYPT_DEFAULT_N: u64 = 1 << 20;
+pub(crate) const SCRYPT_DEFAULT_R: u32 = 8;
+pub(crate) const SCRYPT_DEFAULT_P: u32 = 1;
+pub(crate) const SCRYPT_DEFAULT_MAXMEM: u64 = 1025 * 1024 * 1024;
+
+pub(crate) fn check_arguments(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    length_bits: u64,
+) -> Result<(), KdfError> {
+    match type_ {
+        KdfType::KeyBased => check_kbkdf(args, length_bits),
+        KdfType::Pbkdf2 => check_pbkdf2(args, length_bits),
+        KdfType::SingleStep => check_sskdf(args, length_bits),
+        KdfType::Scrypt => check_scrypt(args),
+        KdfType::X942Concat => check_x942_concat(args),
+        KdfType::Srtp | KdfType::Srtcp => check_srtp(type_, args),
+        _ => Ok(()),
+    }
+}
+
+// SP800-56C section 4.1 leaves H_outputBits of KMAC open, use the lengths OpenSSL allows
+fn check_sskdf(args: &[&KdfArgument], length_bits: u64) -> Result<(), KdfError> {
+    let mut is_kmac = false;
+    let mut maclen: Option<usize> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Mac(mac) => {
+                is_kmac = matches!(mac, KdfMacType::Kmac128 | KdfMacType::Kmac256)
+            }
+            KdfArgument::MacLen(new_maclen) => maclen = Some(*new_maclen),
+            _ => {}
+        }
+    }
+
+    match maclen {
+        Some(maclen)
+            if is_kmac
+                && maclen as u64 * 8 != length_bits
+                && !SSKDF_KMAC_MACLENS.contains(&maclen) =>
+        {
+            Err(KdfError::InvalidOption(
+                "MacLen must be the output length or one of 20, 28, 32, 48 and 64 with KMAC",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+// SP800-108 section 5: n = ceil(L / h) must fit in the r-bit counter, and L in its field
+fn check_kbkdf(args: &[&KdfArgument], length_bits: u64) -> Result<(), KdfError> {
+    let mut r = KBKDF_DEFAULT_R;
+    let mut lbits = KBKDF_DEFAULT_LBITS;
+    let mut use_counter = true;
+    let mut use_l = true;
+    let mut l_units = KdfLUnits::Bits;
+    let mut mode = KdfKbMode::Counter;
+    let mut has_seed = false;
+    let mut counter_location = KdfCounterLocation::BeforeFixed;
+    let mut is_kmac = false;
+    let mut h: Option<u64> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::KbMode(new_mode) => mode = *new_mode,
+            KdfArgument::KbSeed(_) => has_seed = true,
+            KdfArgument::R(new_r) => r = *new_r,
+            KdfArgument::CounterLocation(new_location) => counter_location = *new_location,
+            KdfArgument::LBits(new_lbits) => lbits = *new_lbits,
+            KdfArgument::UseCounter(new_use_counter) => use_counter = *new_use_counter,
+            KdfArgument::UseL(new_use_l) => use_l = *new_use_l,
+            KdfArgument::LUnits(new_l_units) => l_units = *new_l_units,
+            // Extendable output functions have no fixed output length to use as HMAC output
+            KdfArgument::Mac(KdfMacType::Hmac(md))
+                if matches!(md.type_(), Nid::SHAKE128 | Nid::SHAKE256) =>
+            {
+                return Err(KdfError::InvalidOption(
+                    "HMAC requires a fixed output length digest",
+                ));
+            }
+            KdfArgument::Mac(KdfMacType::Hmac(md)) => {
+                is_kmac = false;
+                h = Some(md.size() as u64 * 8);
+            }
+            // Stream ciphers report a block size of 1 and have no block to use as CMAC output
+            KdfArgument::Mac(KdfMacType::Cmac(cipher)) if cipher.block_size() == 1 => {
+                return Err(KdfError::InvalidOption("CMAC requires a block cipher"));
+            }
+            KdfArgument::Mac(KdfMacType::Cmac(cipher)) => {
+                is_kmac = false;
+                h = Some(cipher.block_size() as u64 * 8);
+            }
+            // KMAC derives the output in one call, without a counter or L field
+            KdfArgument::Mac(KdfMacType::Kmac128 | KdfMacType::Kmac256) => {
+                is_kmac = true;
+                h = None;
+            }
+            _ => {}
+        }
+    }
+
+    check_kbkdf_mode(mode, has_seed, counter_location)?;
+    if is_kmac {
+        check_kbkdf_kmac(mode, args)?;
+    }
+
+    check_kbkdf_r(r)?;
+    check_kbkdf_lbits(lbits)?;
+
+    let h = match h {
+        Some(h) => h,
+        None => return Ok(()),
+    };
+
+    if use_counter {
+        let limit = 1u64
+            .checked_shl(u32::from(r))
+            .ok_or(KdfError::InvalidOption("R must be 8, 16, 24 or 32"))?;
+        let max_bits = (limit - 1).saturating_mul(h);
+        if length_bits > max_bits {
+            return Err(KdfError::OutputTooLong(length_bits, max_bits));
+        }
+    }
+
+    if use_l {
+        let l_value = match l_units {
+            KdfLUnits::Bits => length_bits,
+            KdfLUnits::Bytes => length_bits / 8,
+        };
+        let limit = 1u64
+            .checked_shl(u32::from(lbits))
+            .ok_or(KdfError::InvalidOption("LBits must be 8, 16, 24 or 32"))?;
+        if l_value >= limit {
+            return Err(KdfError::LengthNotEncodable(l_value, lbits));
+        }
+    }
+
+    Ok(())
+}
+
+// Only feedback mode starts from an IV, and only feedback and double-pipeline mode have an
+// iteration variable for the counter to precede
+pub(crate) fn check_kbkdf_mode(
+    mode: KdfKbMode,
+    has_seed: bool,
+    counter_location: KdfCounterLocation,
+) -> Result<(), KdfError> {
+    if has_seed && !matches!(mode, KdfKbMode::Feedback) {
+        return Err(KdfError::InvalidOption(
+            "Seed is only used in feedback mode",
+        ));
+    }
+    if matches!(counter_location, KdfCounterLocation::BeforeIter)
+        && matches!(mode, KdfKbMode::Counter)
+    {
+        return Err(KdfError::InvalidOption(
+            "Counter mode has no iteration variable to place the counter before",
+        ));
+    }
+    Ok(())
+}
+
+// SP800-108r1 section 4.4 only defines KMAC in counter mode, as a single call with the label
+// as customization string
+pub(crate) fn check_kbkdf_kmac(mode: KdfKbMode, args: &[&KdfArgument]) -> Result<(), KdfError> {
+    if !matches!(mode, KdfKbMode::Counter) {
+        return Err(KdfError::InvalidOption(
+            "KMAC is only defined in counter mode",
+        ));
+    }
+    if args.iter().any(|arg| is_kbkdf_encoding_option(arg)) {
+        return Err(KdfError::InvalidOption(
+            "KMAC does not use the counter or the fixed input encoding options",
+        ));
+    }
+    Ok(())
+}
+
+// The options that shape the counter and the fixed input data of the HMAC and CMAC PRFs
+fn is_kbkdf_encoding_option(arg: &KdfArgument) -> bool {
+    matches!(
+        arg,
+        KdfArgument::R(_)
+            | KdfArgument::UseCounter(_)
+            | KdfArgument::CounterLocation(_)
+            | KdfArgument::CounterEndianness(_)
+            | KdfArgument::UseSeparator(_)
+            | KdfArgument::UseL(_)
+            | KdfArgument::LBits(_)
+            | KdfArgument::LEndianness(_)
+            | KdfArgument::LUnits(_)
+            | KdfArgument::LPosition(_)
+    )
+}
+
+pub(crate) fn check_kbkdf_r(r: u8) -> Result<(), KdfError> {
+    if !KBKDF_VALID_WIDTHS.contains(&r) {
+        return Err(KdfError::InvalidOption("R must be 8, 16, 24 or 32"));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_kbkdf_lbits(lbits: u8) -> Result<(), KdfError> {
+    if !KBKDF_VALID_WIDTHS.contains(&lbits) {
+        return Err(KdfError::InvalidOption("LBits must be 8, 16, 24 or 32"));
+    }
+    Ok(())
+}
+
+fn check_pbkdf2(args: &[&KdfArgument], length_bits: u64) -> Result<(), KdfError> {
+    let mut pkcs5 = false;
+    let mut salt_len: Option<usize> = None;
+    let mut iterations: Option<u64> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Pkcs5(new_pkcs5) => pkcs5 = *new_pkcs5,
+            KdfArgument::Salt(salt) => salt_len = Some(salt.len()),
+            KdfArgument::Iterations(iter) => iterations = Some(*iter),
+            _ => {}
+        }
+    }
+
+    if pkcs5 {
+        return Ok(());
+    }
+
+    if length_bits < PBKDF2_MIN_KEY_BITS {
+        return Err(KdfError::InvalidOption(
+            "PBKDF2 output length below SP800-132 minimum",
+        ));
+    }
+    if let Some(salt_len) = salt_len {
+        if salt_len * 8 < PBKDF2_MIN_SALT_BITS {
+            return Err(KdfError::InvalidOption(
+                "PBKDF2 salt length below SP800-132 minimum",
+            ));
+        }
+    }
+    if let Some(iterations) = iterations {
+        if iterations < PBKDF2_MIN_ITERATIONS {
+            return Err(KdfError::InvalidOption(
+                "PBKDF2 iteration count below SP800-132 minimum",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_scrypt(args: &[&KdfArgument]) -> Result<(), KdfError> {
+    let mut n = SCRYPT_DEFAULT_N;
+    let mut r = SCRYPT_DEFAULT_R;
+    let mut p = SCRYPT_DEFAULT_P;
+    let mut maxmem = SCRYPT_DEFAULT_MAXMEM;
+
+    for arg in args {
+        match arg {
+            KdfArgument::ScryptN(new_n) => n = *new_n,
+            KdfArgument::ScryptR(new_r) => r = *new_r,
+            KdfArgument::ScryptP(new_p) => p = *new_p,
+            KdfArgument::MaxMemBytes(new_maxmem) => maxmem = *new_maxmem,
+            _ => {}
+        }
+    }
+
+    if n < 2 || !n.is_power_of_two() {
+        return Err(KdfError::InvalidOption(
+            "scrypt N must be a power of two above 1",
+        ));
+    }
+    if r == 0 || p == 0 {
+        return Err(KdfError::InvalidOption("scrypt r and p must be non-zero"));
+    }
+
+    // RFC 7914 section 6: B is p * 128 * r bytes, V is N * 128 * r bytes, and the
+    // OpenSSL implementation uses another 256 * r bytes of scratch space for X and T
+    let r = u128::from(r);
+    let required = 128 * r * (u128::from(n) + u128::from(p) + 2);
+    if required > u128::from(maxmem) {
+        return Err(KdfError::MemoryLimitExceeded(
+            u64::try_from(required).unwrap_or(u64::MAX),
+            maxmem,
+        ));
+    }
+
+    Ok(())
+}
+
+// OpenSSL silently ignores these for the concatenation form, as it does not build an OtherInfo
+fn check_x942_concat(args: &[&KdfArgument]) -> Result<(), KdfError> {
+    for arg in args {
+        match arg {
+            KdfArgument::PartyUInfo(_)
+            | KdfArgument::PartyVInfo(_)
+            | KdfArgument::SuppPubInfo(_)
+            | KdfArgument::SuppPrivInfo(_)
+            | KdfArgument::UseKeyBits(_)
+            | KdfArgument::CekAlg(_) => {
+                return Err(KdfError::InvalidOption(
+                    "Argument only applicable to the X9.42 ASN.1 KDF",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn check_srtp(type_: KdfType, args: &[&KdfArgument]) -> Result<(), KdfError> {
+    let index_len = if matches!(type_, KdfType::Srtcp) {
+        SRTCP_INDEX_BYTES
+    } else {
+        SRTP_INDEX_BYTES
+    };
+
+    for arg in args {
+        match arg {
+            KdfArgument::SrtpKdr(kdr)
+                if *kdr != 0 && (!kdr.is_power_of_two() || *kdr > SRTP_MAX_KDR) =>
+            {
+                return Err(KdfError::InvalidOption(
+                    "SrtpKdr must be zero or a power of two up to 2^24",
+                ));
+            }
+            KdfArgument::SrtpIndex(index) if index.len() != index_len => {
+                return Err(KdfError::InvalidOption("SrtpIndex has the wrong length"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,410 @@
+use std::convert::TryFrom;
+
+use super::blake2b::{blake2b, Blake2b, MAX_OUTPUT_BYTES};
+use crate::{KdfArgument, KdfError, KdfType};
+
+// Defaults as used by the OpenSSL Argon2 implementation
+const DEFAULT_ITERATIONS: u32 = 3;
+const DEFAULT_MEMORY_COST: u32 = 2 * SYNC_POINTS;
+const DEFAULT_LANES: u32 = 1;
+
+const VERSION: u32 = 0x13;
+const SYNC_POINTS: u32 = 4;
+const BLOCK_WORDS: usize = 128;
+const BLOCK_BYTES: usize = BLOCK_WORDS * 8;
+const MIN_SALT_BYTES: usize = 8;
+const MIN_OUTPUT_BYTES: usize = 4;
+
+type Block = [u64; BLOCK_WORDS];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Variant {
+    Argon2d = 0,
+    Argon2i = 1,
+    Argon2id = 2,
+}
+
+struct Instance {
+    variant: Variant,
+    passes: u32,
+    lanes: u32,
+    memory_blocks: u32,
+    segment_length: u32,
+    lane_length: u32,
+    memory: Vec<Block>,
+}
+
+pub(super) fn perform(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    length: usize,
+) -> Result<Vec<u8>, KdfError> {
+    let variant = match type_ {
+        KdfType::Argon2d => Variant::Argon2d,
+        KdfType::Argon2i => Variant::Argon2i,
+        KdfType::Argon2id => Variant::Argon2id,
+        _ => return Err(KdfError::Unimplemented("Non-Argon2 KDF")),
+    };
+
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut memory_cost = DEFAULT_MEMORY_COST;
+    let mut lanes = DEFAULT_LANES;
+    let mut password: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+    let mut secret: &[u8] = &[];
+    let mut ad: &[u8] = &[];
+
+    for arg in args {
+        match arg {
+            KdfArgument::Password(new_password) => {
+                password = Some(new_password);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::Secret(new_secret) => {
+                secret = new_secret;
+            }
+            KdfArgument::AssociatedData(new_ad) => {
+                ad = new_ad;
+            }
+            KdfArgument::Iterations(new_iterations) => {
+                iterations = u32::try_from(*new_iterations)
+                    .map_err(|_| KdfError::InvalidOption("Iterations too large"))?;
+            }
+            KdfArgument::MemoryCost(new_memory_cost) => {
+                memory_cost = *new_memory_cost;
+            }
+            KdfArgument::Lanes(new_lanes) => {
+                lanes = *new_lanes;
+            }
+            KdfArgument::Threads(_) => {
+                // Lanes are computed sequentially, the output does not depend on this
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to Argon2"));
+            }
+        }
+    }
+
+    let password = password.ok_or(KdfError::MissingArgument("Password"))?;
+    let salt = salt.ok_or(KdfError::MissingArgument("Salt"))?;
+
+    if salt.len() < MIN_SALT_BYTES {
+        return Err(KdfError::InvalidOption("Argon2 salt too short"));
+    }
+    if length < MIN_OUTPUT_BYTES {
+        return Err(KdfError::InvalidOption("Argon2 output length too short"));
+    }
+    if iterations == 0 {
+        return Err(KdfError::InvalidOption(
+            "Argon2 iterations must be non-zero",
+        ));
+    }
+    if lanes == 0 || lanes > 0xFFFFFF {
+        return Err(KdfError::InvalidOption("Argon2 lanes out of range"));
+    }
+    if memory_cost < 2 * SYNC_POINTS * lanes {
+        return Err(KdfError::InvalidOption(
+            "Argon2 memory cost too low for lanes",
+        ));
+    }
+    let tag_length =
+        u32::try_from(length).map_err(|_| KdfError::InvalidOption("length too long"))?;
+
+    // RFC 9106 section 3.2, step 1
+    let h0 = blake2b(
+        MAX_OUTPUT_BYTES,
+        &[
+            &lanes.to_le_bytes(),
+            &tag_length.to_le_bytes(),
+            &memory_cost.to_le_bytes(),
+            &iterations.to_le_bytes(),
+            &VERSION.to_le_bytes(),
+            &(variant as u32).to_le_bytes(),
+            &len_le_bytes(password)?,
+            password,
+            &len_le_bytes(salt)?,
+            salt,
+            &len_le_bytes(secret)?,
+            secret,
+            &len_le_bytes(ad)?,
+            ad,
+        ],
+    );
+
+    let segment_length = memory_cost / (lanes * SYNC_POINTS);
+    let memory_blocks = segment_length * lanes * SYNC_POINTS;
+    let mut instance = Instance {
+        variant,
+        passes: iterations,
+        lanes,
+        memory_blocks,
+        segment_length,
+        lane_length: segment_length * SYNC_POINTS,
+        memory: vec![[0; BLOCK_WORDS]; memory_blocks as usize],
+    };
+
+    instance.fill_first_blocks(&h0);
+    for pass in 0..instance.passes {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..instance.lanes {
+                instance.fill_segment(pass, lane, slice);
+            }
+        }
+    }
+
+    let mut final_block = instance.memory[instance.lane_length as usize - 1];
+    for lane in 1..instance.lanes {
+        let last = instance.lane_length * (lane + 1) - 1;
+        xor_block(&mut final_block, &instance.memory[last as usize]);
+    }
+
+    Ok(h_prime(length, &[&block_to_bytes(&final_block)]))
+}
+
+fn len_le_bytes(input: &[u8]) -> Result<[u8; 4], KdfError> {
+    let len =
+        u32::try_from(input.len()).map_err(|_| KdfError::InvalidOption("Argon2 input too long"))?;
+    Ok(len.to_le_bytes())
+}
+
+// The variable-length hash function H' from RFC 9106 section 3.3
+fn h_prime(length: usize, input: &[&[u8]]) -> Vec<u8> {
+    let length_bytes = (length as u32).to_le_bytes();
+
+    let mut hasher = Blake2b::new(std::cmp::min(length, MAX_OUTPUT_BYTES));
+    hasher.update(&length_bytes);
+    for part in input {
+        hasher.update(part);
+    }
+    let mut v = hasher.finalize();
+
+    if length <= MAX_OUTPUT_BYTES {
+        return v;
+    }
+
+    let r = length.div_ceil(32) - 2;
+    let mut output = Vec::with_capacity(length);
+    output.extend_from_slice(&v[..32]);
+    for _ in 1..r {
+        v = blake2b(MAX_OUTPUT_BYTES, &[&v]);
+        output.extend_from_slice(&v[..32]);
+    }
+    output.extend_from_slice(&blake2b(length - 32 * r, &[&v]));
+
+    output
+}
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks_exact(8)) {
+        let mut word_bytes = [0; 8];
+        word_bytes.copy_from_slice(chunk);
+        *word = u64::from_le_bytes(word_bytes);
+    }
+    block
+}
+
+fn block_to_bytes(block: &Block) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(BLOCK_BYTES);
+    for word in block {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+fn xor_block(dst: &mut Block, src: &Block) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+fn f_bla_mka(x: u64, y: u64) -> u64 {
+    let xy = (x & 0xFFFFFFFF) * (y & 0xFFFFFFFF);
+    x.wrapping_add(y).wrapping_add(xy.wrapping_mul(2))
+}
+
+fn g(v: &mut Block, a: usize, b: usize, c: usize, d: usize) {
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// The permutation P from RFC 9106 section 3.6, applied to 16 words of the block
+fn permute(v: &mut Block, idx: [usize; 16]) {
+    g(v, idx[0], idx[4], idx[8], idx[12]);
+    g(v, idx[1], idx[5], idx[9], idx[13]);
+    g(v, idx[2], idx[6], idx[10], idx[14]);
+    g(v, idx[3], idx[7], idx[11], idx[15]);
+    g(v, idx[0], idx[5], idx[10], idx[15]);
+    g(v, idx[1], idx[6], idx[11], idx[12]);
+    g(v, idx[2], idx[7], idx[8], idx[13]);
+    g(v, idx[3], idx[4], idx[9], idx[14]);
+}
+
+// The compression function G from RFC 9106 section 3.5
+fn compress(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut r = *reference;
+    xor_block(&mut r, prev);
+
+    let mut tmp = r;
+    if with_xor {
+        xor_block(&mut tmp, next);
+    }
+
+    for i in 0..8 {
+        let mut idx = [0; 16];
+        for (j, x) in idx.iter_mut().enumerate() {
+            *x = 16 * i + j;
+        }
+        permute(&mut r, idx);
+    }
+    for i in 0..8 {
+        let mut idx = [0; 16];
+        for (j, x) in idx.iter_mut().enumerate() {
+            *x = 2 * i + (j / 2) * 16 + (j % 2);
+        }
+        permute(&mut r, idx);
+    }
+
+    *next = tmp;
+    xor_block(next, &r);
+}
+
+impl Instance {
+    fn fill_first_blocks(&mut self, h0: &[u8]) {
+        for lane in 0..self.lanes {
+            for i in 0..2u32 {
+                let block = h_prime(BLOCK_BYTES, &[h0, &i.to_le_bytes(), &lane.to_le_bytes()]);
+                let offset = lane * self.lane_length + i;
+                self.memory[offset as usize] = block_from_bytes(&block);
+            }
+        }
+    }
+
+    fn fill_segment(&mut self, pass: u32, lane: u32, slice: u32) {
+        let data_independent = match self.variant {
+            Variant::Argon2i => true,
+            Variant::Argon2id => pass == 0 && slice < SYNC_POINTS / 2,
+            Variant::Argon2d => false,
+        };
+
+        let zero_block: Block = [0; BLOCK_WORDS];
+        let mut input_block: Block = [0; BLOCK_WORDS];
+        let mut address_block: Block = [0; BLOCK_WORDS];
+        if data_independent {
+            input_block[0] = pass.into();
+            input_block[1] = lane.into();
+            input_block[2] = slice.into();
+            input_block[3] = self.memory_blocks.into();
+            input_block[4] = self.passes.into();
+            input_block[5] = self.variant as u64;
+        }
+
+        let mut starting_index = 0;
+        if pass == 0 && slice == 0 {
+            // The first two blocks of each lane have already been generated
+            starting_index = 2;
+            if data_independent {
+                next_addresses(&mut address_block, &mut input_block, &zero_block);
+            }
+        }
+
+        for index in starting_index..self.segment_length {
+            let curr_offset = lane * self.lane_length + slice * self.segment_length + index;
+            // The first block of a lane follows the last block of the same lane
+            let prev_offset = if curr_offset % self.lane_length == 0 {
+                curr_offset + self.lane_length - 1
+            } else {
+                curr_offset - 1
+            };
+
+            let pseudo_rand = if data_independent {
+                let address_index = index as usize % BLOCK_WORDS;
+                if address_index == 0 {
+                    next_addresses(&mut address_block, &mut input_block, &zero_block);
+                }
+                address_block[address_index]
+            } else {
+                self.memory[prev_offset as usize][0]
+            };
+
+            let ref_lane = if pass == 0 && slice == 0 {
+                // Other lanes can not be referenced yet
+                lane
+            } else {
+                ((pseudo_rand >> 32) % u64::from(self.lanes)) as u32
+            };
+            let ref_index = self.index_alpha(
+                pass,
+                slice,
+                index,
+                pseudo_rand & 0xFFFFFFFF,
+                ref_lane == lane,
+            );
+
+            let reference = self.memory[(self.lane_length * ref_lane + ref_index) as usize];
+            let prev = self.memory[prev_offset as usize];
+            compress(
+                &prev,
+                &reference,
+                &mut self.memory[curr_offset as usize],
+                pass != 0,
+            );
+        }
+    }
+
+    // Determines the reference block index within the lane, RFC 9106 section 3.4.2
+    fn index_alpha(
+        &self,
+        pass: u32,
+        slice: u32,
+        index: u32,
+        pseudo_rand: u64,
+        same_lane: bool,
+    ) -> u32 {
+        let reference_area_size = if pass == 0 {
+            if slice == 0 {
+                index - 1
+            } else if same_lane {
+                slice * self.segment_length + index - 1
+            } else if index == 0 {
+                slice * self.segment_length - 1
+            } else {
+                slice * self.segment_length
+            }
+        } else if same_lane {
+            self.lane_length - self.segment_length + index - 1
+        } else if index == 0 {
+            self.lane_length - self.segment_length - 1
+        } else {
+            self.lane_length - self.segment_length
+        };
+        let reference_area_size = u64::from(reference_area_size);
+
+        let relative_position = (pseudo_rand * pseudo_rand) >> 32;
+        let relative_position =
+            reference_area_size - 1 - ((reference_area_size * relative_position) >> 32);
+
+        let start_position = if pass != 0 && slice != SYNC_POINTS - 1 {
+            u64::from((slice + 1) * self.segment_length)
+        } else {
+            0
+        };
+
+        ((start_position + relative_position) % u64::from(self.lane_length)) as u32
+    }
+}
+
+fn next_addresses(address_block: &mut Block, input_block: &mut Block, zero_block: &Block) {
+    input_block[6] += 1;
+    compress(zero_block, input_block, address_block, false);
+    let tmp = *address_block;
+    compress(zero_block, &tmp, address_block, false);
+}
@@ -0,0 +1,140 @@
+// BLAKE2b as specified in RFC 7693, needed for Argon2 with variable output lengths,
+// which OpenSSL does not expose through its BLAKE2B-512 digest.
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+const BLOCK_BYTES: usize = 128;
+pub(super) const MAX_OUTPUT_BYTES: usize = 64;
+
+pub(super) struct Blake2b {
+    h: [u64; 8],
+    t: u128,
+    buf: [u8; BLOCK_BYTES],
+    buf_len: usize,
+    out_len: usize,
+}
+
+impl Blake2b {
+    pub(super) fn new(out_len: usize) -> Self {
+        assert!(out_len > 0 && out_len <= MAX_OUTPUT_BYTES);
+
+        let mut h = IV;
+        h[0] ^= 0x01010000 ^ out_len as u64;
+
+        Blake2b {
+            h,
+            t: 0,
+            buf: [0; BLOCK_BYTES],
+            buf_len: 0,
+            out_len,
+        }
+    }
+
+    pub(super) fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            // The last block needs to be processed by finalize, so only compress a full
+            // buffer when there is more input to follow.
+            if self.buf_len == BLOCK_BYTES {
+                self.t += BLOCK_BYTES as u128;
+                let block = self.buf;
+                self.compress(&block, false);
+                self.buf_len = 0;
+            }
+
+            let take = std::cmp::min(BLOCK_BYTES - self.buf_len, input.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&input[..take]);
+            self.buf_len += take;
+            input = &input[take..];
+        }
+    }
+
+    pub(super) fn finalize(mut self) -> Vec<u8> {
+        self.t += self.buf_len as u128;
+        let mut block = self.buf;
+        block[self.buf_len..].iter_mut().for_each(|b| *b = 0);
+        self.compress(&block, true);
+
+        let mut output = Vec::with_capacity(MAX_OUTPUT_BYTES);
+        for word in &self.h {
+            output.extend_from_slice(&word.to_le_bytes());
+        }
+        output.truncate(self.out_len);
+        output
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_BYTES], last: bool) {
+        let mut m = [0u64; 16];
+        for (i, word) in m.iter_mut().enumerate() {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&block[i * 8..(i + 1) * 8]);
+            *word = u64::from_le_bytes(bytes);
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.t as u64;
+        v[13] ^= (self.t >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for s in &SIGMA {
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+pub(super) fn blake2b(out_len: usize, input: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Blake2b::new(out_len);
+    for part in input {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
@@ -0,0 +1,95 @@
+use openssl::hash::MessageDigest;
+
+use super::{get_digest_length_bytes, hmac};
+use crate::{KdfArgument, KdfError, KdfHkdfMode};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut mode = KdfHkdfMode::ExtractAndExpand;
+    let mut md: Option<MessageDigest> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+    let mut info: Vec<u8> = Vec::new();
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::Info(new_info) => {
+                info.extend_from_slice(new_info);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::HkdfMode(new_mode) => {
+                mode = *new_mode;
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to HKDF"));
+            }
+        }
+    }
+
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let h = get_digest_length_bytes(md)?;
+
+    match mode {
+        KdfHkdfMode::ExtractOnly => {
+            if length != h {
+                return Err(KdfError::InvalidOption(
+                    "length must equal digest size for extract-only",
+                ));
+            }
+            Ok(extract(md, h, salt, key)?)
+        }
+        KdfHkdfMode::ExpandOnly => expand(md, h, key, &info, length),
+        KdfHkdfMode::ExtractAndExpand => {
+            let prk = extract(md, h, salt, key)?;
+            expand(md, h, &prk, &info, length)
+        }
+    }
+}
+
+pub(super) fn extract(
+    md: MessageDigest,
+    h: usize,
+    salt: Option<&[u8]>,
+    ikm: &[u8],
+) -> Result<Vec<u8>, KdfError> {
+    // RFC 5869 section 2.2: if the salt is not provided, it is set to HashLen zeros
+    let zero_salt = vec![0; h];
+    let salt = match salt {
+        Some(salt) if !salt.is_empty() => salt,
+        _ => &zero_salt,
+    };
+
+    Ok(hmac(md, salt, &[ikm])?)
+}
+
+pub(super) fn expand(
+    md: MessageDigest,
+    h: usize,
+    prk: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, KdfError> {
+    let n = length.div_ceil(h);
+    if n > 255 {
+        return Err(KdfError::InvalidOption("length too long for HKDF"));
+    }
+
+    let mut output = Vec::with_capacity(n * h);
+    let mut t: Vec<u8> = Vec::new();
+
+    for i in 1..=n {
+        t = hmac(md, prk, &[&t, info, &[i as u8]])?;
+        output.extend_from_slice(&t);
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
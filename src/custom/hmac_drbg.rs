@@ -0,0 +1,35 @@
+use openssl::hash::MessageDigest;
+
+use crate::{hmac_drbg::HmacDrbg, KdfArgument, KdfError};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut entropy: Option<&[u8]> = None;
+    let mut nonce: Option<&[u8]> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::Entropy(new_entropy) => {
+                entropy = Some(new_entropy);
+            }
+            KdfArgument::Nonce(new_nonce) => {
+                nonce = Some(new_nonce);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to HMAC-DRBG-KDF",
+                ));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let entropy = entropy.ok_or(KdfError::MissingArgument("Entropy"))?;
+    let nonce = nonce.ok_or(KdfError::MissingArgument("Nonce"))?;
+
+    let mut drbg = HmacDrbg::new(md, entropy, nonce)?;
+    Ok(drbg.generate(length)?)
+}
@@ -0,0 +1,254 @@
+use openssl::{error::ErrorStack, pkey::PKey, sign::Signer};
+
+use super::{
+    get_cipher_length_bytes, get_digest_length_bytes,
+    kmac::{kmac, Kmac},
+};
+use crate::{
+    checks, KdfArgument, KdfCounterLocation, KdfEndianness, KdfError, KdfKbMode, KdfLPosition,
+    KdfLUnits, KdfMacType,
+};
+
+type Prf<'a> = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, ErrorStack> + 'a>;
+type PrfFactory<'a> = Box<dyn Fn(&[u8]) -> Result<Prf<'a>, ErrorStack> + 'a>;
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    perform_bits(args, length * 8)
+}
+
+// SP800-108 defines L in bits, the output is the leftmost length_bits bits
+pub(super) fn perform_bits<'a>(
+    args: &[&'a KdfArgument],
+    length_bits: usize,
+) -> Result<Vec<u8>, KdfError> {
+    let length = length_bits.div_ceil(8);
+    let mut mode = KdfKbMode::Counter;
+    let mut use_counter = true;
+    let mut counter_location = KdfCounterLocation::BeforeFixed;
+    let mut use_separator = true;
+    let mut use_l = true;
+    let mut r: u8 = 32;
+    let mut lbits: u8 = 32;
+    let mut counter_endianness = KdfEndianness::Big;
+    let mut l_endianness = KdfEndianness::Big;
+    let mut l_units = KdfLUnits::Bits;
+    let mut l_position = KdfLPosition::AfterContext;
+    let mut key: Option<&'a [u8]> = None;
+    let mut label: Option<&'a [u8]> = None;
+    let mut context: Option<&'a [u8]> = None;
+    let mut seed: Option<&'a [u8]> = None;
+    let mut h: Option<usize> = None;
+
+    let mut prf: Option<PrfFactory<'a>> = None;
+    let mut kmac_variant: Option<Kmac> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Salt(new_salt) => {
+                label = Some(new_salt);
+            }
+            KdfArgument::KbInfo(new_kb_info) => {
+                context = Some(new_kb_info);
+            }
+            KdfArgument::R(new_r) => {
+                r = *new_r;
+            }
+            KdfArgument::LBits(new_lbits) => {
+                lbits = *new_lbits;
+            }
+            KdfArgument::CounterEndianness(new_endianness) => {
+                counter_endianness = *new_endianness;
+            }
+            KdfArgument::LEndianness(new_endianness) => {
+                l_endianness = *new_endianness;
+            }
+            KdfArgument::LUnits(new_l_units) => {
+                l_units = *new_l_units;
+            }
+            KdfArgument::LPosition(new_l_position) => {
+                l_position = *new_l_position;
+            }
+            KdfArgument::UseL(new_use_l) => {
+                use_l = *new_use_l;
+            }
+            KdfArgument::UseSeparator(new_use_separator) => {
+                use_separator = *new_use_separator;
+            }
+            KdfArgument::UseCounter(new_use_counter) => {
+                use_counter = *new_use_counter;
+            }
+            KdfArgument::CounterLocation(new_counter_location) => {
+                counter_location = *new_counter_location;
+            }
+            KdfArgument::Mac(mac) => match mac {
+                KdfMacType::Hmac(md) => {
+                    kmac_variant = None;
+                    h = Some(get_digest_length_bytes(*md)? * 8);
+                    prf = Some(Box::new(move |key| {
+                        let hmac_key = PKey::hmac(key)?;
+                        Ok(Box::new(move |input| {
+                            let mut signer = Signer::new(*md, &hmac_key)?;
+                            signer.update(input)?;
+                            signer.sign_to_vec()
+                        }))
+                    }));
+                }
+                KdfMacType::Cmac(cipher) => {
+                    kmac_variant = None;
+                    h = Some(get_cipher_length_bytes(*cipher)? * 8);
+                    prf = Some(Box::new(move |key| {
+                        let cmac_key = PKey::cmac(cipher, key)?;
+                        Ok(Box::new(move |input| {
+                            let mut signer = Signer::new_without_digest(&cmac_key)?;
+                            signer.update(input)?;
+                            signer.sign_to_vec()
+                        }))
+                    }));
+                }
+                KdfMacType::Kmac128 => {
+                    kmac_variant = Some(Kmac::Kmac128);
+                }
+                KdfMacType::Kmac256 => {
+                    kmac_variant = Some(Kmac::Kmac256);
+                }
+            },
+            KdfArgument::KbMode(new_mode) => {
+                mode = *new_mode;
+            }
+            KdfArgument::KbSeed(new_seed) => {
+                seed = Some(new_seed);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to KBKDF"));
+            }
+        }
+    }
+
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    checks::check_kbkdf_mode(mode, seed.is_some(), counter_location)?;
+
+    // SP800-108r1 section 4.4: KMAC derives the whole output at once, with the label as
+    // customization string, so the counter and L encoding options are rejected
+    if let Some(variant) = kmac_variant {
+        checks::check_kbkdf_kmac(mode, args)?;
+        if length_bits % 8 != 0 {
+            return Err(KdfError::UnsupportedOption(format!(
+                "KMAC output length of {} bits",
+                length_bits
+            )));
+        }
+        return Ok(kmac(
+            variant,
+            key,
+            &[context.unwrap_or_default()],
+            length,
+            label.unwrap_or_default(),
+        ));
+    }
+
+    let prf = prf.ok_or(KdfError::MissingArgument("Mac"))?;
+    let h = h.ok_or(KdfError::MissingArgument("h"))?;
+
+    // The counter and L are encoded in whole bytes of a u64
+    checks::check_kbkdf_r(r)?;
+    checks::check_kbkdf_lbits(lbits)?;
+
+    // The limits on n and L have been enforced by checks::check_arguments
+    let n = length_bits.div_ceil(h) as u64;
+    let l_value = match l_units {
+        KdfLUnits::Bits => length_bits,
+        KdfLUnits::Bytes if length_bits % 8 == 0 => length,
+        KdfLUnits::Bytes => {
+            return Err(KdfError::InvalidOption(
+                "L in bytes requires a whole number of output bytes",
+            ));
+        }
+    };
+    let l2 = encode_integer(l_value as u64, (lbits / 8) as usize, l_endianness);
+
+    let mut fixed_input = Vec::new();
+    if let Some(label) = label {
+        fixed_input.extend_from_slice(label);
+    }
+    if use_separator {
+        fixed_input.extend_from_slice(&[00]);
+    }
+    if use_l && matches!(l_position, KdfLPosition::BeforeContext) {
+        fixed_input.extend_from_slice(&l2);
+    }
+    if let Some(context) = context {
+        fixed_input.extend_from_slice(context);
+    }
+    if use_l && matches!(l_position, KdfLPosition::AfterContext) {
+        fixed_input.extend_from_slice(&l2);
+    }
+
+    if !use_counter && matches!(mode, KdfKbMode::Counter) {
+        return Err(KdfError::InvalidOption("Counter mode requires the counter"));
+    }
+    let counter_offset = match counter_location {
+        KdfCounterLocation::BeforeFixed | KdfCounterLocation::BeforeIter => 0,
+        KdfCounterLocation::AfterFixed => fixed_input.len(),
+        KdfCounterLocation::MiddleFixed(offset) if offset <= fixed_input.len() => offset,
+        KdfCounterLocation::MiddleFixed(_) => {
+            return Err(KdfError::InvalidOption(
+                "Counter offset is beyond the fixed input data",
+            ));
+        }
+    };
+
+    let mut output = Vec::new();
+
+    let prf = prf(key)?;
+
+    // In feedback mode, K(0) is the IV and each block starts with the previous one
+    let mut previous = seed.unwrap_or_default().to_vec();
+    // In double-pipeline mode, A(0) is the fixed input and each block starts with A(i)
+    let mut pipeline = fixed_input.clone();
+
+    for i in 1..=n {
+        let mut block = Vec::new();
+        let counter = if use_counter {
+            encode_integer(i, (r / 8) as usize, counter_endianness)
+        } else {
+            Vec::new()
+        };
+
+        if matches!(counter_location, KdfCounterLocation::BeforeIter) {
+            block.extend_from_slice(&counter);
+        }
+        match mode {
+            KdfKbMode::Counter => {}
+            KdfKbMode::Feedback => block.extend_from_slice(&previous),
+            KdfKbMode::DoublePipeline => {
+                pipeline = prf(&pipeline)?;
+                block.extend_from_slice(&pipeline);
+            }
+        }
+        block.extend_from_slice(&fixed_input[..counter_offset]);
+        if !matches!(counter_location, KdfCounterLocation::BeforeIter) {
+            block.extend_from_slice(&counter);
+        }
+        block.extend_from_slice(&fixed_input[counter_offset..]);
+
+        previous = prf(&block)?;
+        output.extend_from_slice(&previous);
+    }
+
+    output.truncate(length);
+    if length_bits % 8 != 0 {
+        output[length - 1] &= 0xff << (8 - length_bits % 8);
+    }
+    Ok(output)
+}
+
+// The low `width` bytes of value, in the given byte order
+fn encode_integer(value: u64, width: usize, endianness: KdfEndianness) -> Vec<u8> {
+    match endianness {
+        KdfEndianness::Big => value.to_be_bytes()[8 - width..].to_vec(),
+        KdfEndianness::Little => value.to_le_bytes()[..width].to_vec(),
+    }
+}
@@ -0,0 +1,209 @@
+// KMAC as specified in NIST SP800-185. OpenSSL only exposes plain SHAKE as a digest,
+// which cannot be used for cSHAKE with a function name, so this has its own Keccak.
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// Rotation offsets and lane order of the combined rho and pi steps
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+// The domain separation bits of cSHAKE followed by the first padding bit
+const CSHAKE_SUFFIX: u8 = 0x04;
+
+fn keccak_f1600(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut last = state[1];
+        for (rho, pi) in RHO.iter().zip(PI.iter()) {
+            let current = state[*pi];
+            state[*pi] = last.rotate_left(*rho);
+            last = current;
+        }
+
+        // chi
+        for y in 0..5 {
+            let row = [
+                state[5 * y],
+                state[5 * y + 1],
+                state[5 * y + 2],
+                state[5 * y + 3],
+                state[5 * y + 4],
+            ];
+            for x in 0..5 {
+                state[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        state[0] ^= round_constant;
+    }
+}
+
+struct Keccak {
+    state: [u64; 25],
+    rate: usize,
+    pos: usize,
+}
+
+impl Keccak {
+    fn new(rate: usize) -> Self {
+        Keccak {
+            state: [0; 25],
+            rate,
+            pos: 0,
+        }
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= u64::from(byte) << (8 * (index % 8));
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        for byte in input {
+            self.xor_byte(self.pos, *byte);
+            self.pos += 1;
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    fn squeeze(mut self, suffix: u8, length: usize) -> Vec<u8> {
+        self.xor_byte(self.pos, suffix);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+
+        let mut output = Vec::with_capacity(length);
+        let mut pos = 0;
+        while output.len() < length {
+            if pos == self.rate {
+                keccak_f1600(&mut self.state);
+                pos = 0;
+            }
+            output.push((self.state[pos / 8] >> (8 * (pos % 8))) as u8);
+            pos += 1;
+        }
+        output
+    }
+}
+
+// left_encode and right_encode from SP800-185 section 2.3.1
+fn encode_length(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|b| **b == 0).count();
+    bytes[skip..].to_vec()
+}
+
+fn left_encode(value: u64) -> Vec<u8> {
+    let mut output = encode_length(value);
+    output.insert(0, output.len() as u8);
+    output
+}
+
+fn right_encode(value: u64) -> Vec<u8> {
+    let mut output = encode_length(value);
+    output.push(output.len() as u8);
+    output
+}
+
+fn absorb_encoded_string(keccak: &mut Keccak, input: &[u8]) -> usize {
+    let encoded_len = left_encode(input.len() as u64 * 8);
+    keccak.absorb(&encoded_len);
+    keccak.absorb(input);
+    encoded_len.len() + input.len()
+}
+
+// bytepad(encode_string(a) || encode_string(b), rate)
+fn absorb_bytepad(keccak: &mut Keccak, strings: &[&[u8]]) {
+    let rate = keccak.rate;
+    let encoded_rate = left_encode(rate as u64);
+    keccak.absorb(&encoded_rate);
+
+    let mut len = encoded_rate.len();
+    for string in strings {
+        len += absorb_encoded_string(keccak, string);
+    }
+    let padding = (rate - len % rate) % rate;
+    keccak.absorb(&vec![0; padding]);
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Kmac {
+    Kmac128,
+    Kmac256,
+}
+
+impl Kmac {
+    fn rate(self) -> usize {
+        match self {
+            Kmac::Kmac128 => 168,
+            Kmac::Kmac256 => 136,
+        }
+    }
+
+    // The default key length used by OpenSSL where KMAC keys a KDF, the rate minus 4
+    pub(super) fn default_key_len(self) -> usize {
+        self.rate() - 4
+    }
+}
+
+pub(super) fn kmac(
+    variant: Kmac,
+    key: &[u8],
+    input: &[&[u8]],
+    length: usize,
+    custom: &[u8],
+) -> Vec<u8> {
+    let mut keccak = Keccak::new(variant.rate());
+
+    absorb_bytepad(&mut keccak, &[b"KMAC", custom]);
+    absorb_bytepad(&mut keccak, &[key]);
+    for part in input {
+        keccak.absorb(part);
+    }
+    keccak.absorb(&right_encode(length as u64 * 8));
+
+    keccak.squeeze(CSHAKE_SUFFIX, length)
+}
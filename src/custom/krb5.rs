@@ -0,0 +1,158 @@
+use openssl::{
+    nid::Nid,
+    symm::{Cipher, Crypter, Mode},
+};
+
+use crate::{KdfArgument, KdfError};
+
+// DES3 derives 168 random bits, which are expanded to a 192-bit key with parity bits
+const DES3_RANDOM_BYTES: usize = 21;
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut cipher: Option<Cipher> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut constant: Option<&[u8]> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Cipher(new_cipher) => {
+                cipher = Some(*new_cipher);
+            }
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Krb5Constant(new_constant) => {
+                constant = Some(new_constant);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to KRB5KDF",
+                ));
+            }
+        }
+    }
+
+    let cipher = cipher.ok_or(KdfError::MissingArgument("Cipher"))?;
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let constant = constant.ok_or(KdfError::MissingArgument("Krb5Constant"))?;
+
+    if key.len() != cipher.key_len() {
+        return Err(KdfError::InvalidOption("Key length does not match cipher"));
+    }
+    if length != cipher.key_len() {
+        return Err(KdfError::InvalidOption(
+            "length must equal the cipher key length",
+        ));
+    }
+    if constant.is_empty() {
+        return Err(KdfError::InvalidOption("Krb5Constant must not be empty"));
+    }
+
+    let is_des3 = cipher.nid() == Nid::DES_EDE3_CBC;
+    let random_len = if is_des3 { DES3_RANDOM_BYTES } else { length };
+
+    // RFC 3961 section 5.1: DR(Key, Constant) = k-truncate(K1 | K2 | ...),
+    // K1 = E(Key, n-fold(Constant)), Kn = E(Key, Kn-1)
+    let block_size = cipher.block_size();
+    let mut block = if constant.len() == block_size {
+        constant.to_vec()
+    } else {
+        nfold(constant, block_size)
+    };
+    let mut output = Vec::with_capacity(random_len);
+
+    while output.len() < random_len {
+        block = encrypt_block(cipher, key, &block)?;
+        output.extend_from_slice(&block);
+    }
+    output.truncate(random_len);
+
+    if is_des3 {
+        des3_random_to_key(&mut output)?;
+    }
+    Ok(output)
+}
+
+fn encrypt_block(cipher: Cipher, key: &[u8], block: &[u8]) -> Result<Vec<u8>, KdfError> {
+    let iv = vec![0; cipher.iv_len().unwrap_or(0)];
+    let mut crypter = Crypter::new(cipher, Mode::Encrypt, key, Some(&iv))?;
+    crypter.pad(false);
+
+    let mut output = vec![0; block.len() + cipher.block_size()];
+    let mut count = crypter.update(block, &mut output)?;
+    count += crypter.finalize(&mut output[count..])?;
+    output.truncate(count);
+    Ok(output)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// n-fold from RFC 3961 section 5.1, following the byte-wise formulation of the MIT reference code
+fn nfold(input: &[u8], out_len: usize) -> Vec<u8> {
+    let in_len = input.len();
+    let in_bits = in_len * 8;
+    let lcm = in_len / gcd(in_len, out_len) * out_len;
+
+    let mut output = vec![0u8; out_len];
+    let mut carry: u32 = 0;
+
+    for i in (0..lcm).rev() {
+        // The most significant bit of this byte, in the input rotated by 13 bits per repetition
+        let msbit =
+            (in_bits - 1 + (in_bits + 13) * (i / in_len) + (in_len - i % in_len) * 8) % in_bits;
+        let high = input[(in_len - 1 - (msbit >> 3)) % in_len] as u32;
+        let low = input[(in_len - (msbit >> 3)) % in_len] as u32;
+
+        carry += (((high << 8) | low) >> ((msbit & 7) + 1)) & 0xff;
+        carry += output[i % out_len] as u32;
+        output[i % out_len] = (carry & 0xff) as u8;
+        carry >>= 8;
+    }
+
+    // Add the remaining carry with end-around carry (ones' complement addition)
+    if carry != 0 {
+        for byte in output.iter_mut().rev() {
+            carry += *byte as u32;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+    }
+
+    output
+}
+
+fn set_odd_parity(byte: u8) -> u8 {
+    let high = byte & 0xfe;
+    high | ((high.count_ones() as u8 + 1) & 1)
+}
+
+// DES3 random-to-key from RFC 3961 section 6.3.1, expanding each 7-byte group in place
+fn des3_random_to_key(key: &mut Vec<u8>) -> Result<(), KdfError> {
+    key.resize(24, 0);
+
+    for i in (0..3).rev() {
+        key.copy_within(i * 7..i * 7 + 7, i * 8);
+        let block = &mut key[i * 8..i * 8 + 8];
+
+        block[7] = 0;
+        for j in 0..7 {
+            block[7] |= (block[j] & 1) << (j + 1);
+        }
+        for byte in block.iter_mut() {
+            *byte = set_odd_parity(*byte);
+        }
+    }
+
+    // Reject keys that degrade triple DES to single DES
+    if key[0..8] == key[8..16] || key[8..16] == key[16..24] {
+        return Err(KdfError::InvalidOption("Derived DES3 key is weak"));
+    }
+
+    Ok(())
+}
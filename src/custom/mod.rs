@@ -1,33 +1,62 @@
-use openssl::{hash::MessageDigest, nid::Nid, pkey::PKey, sign::Signer, symm::Cipher};
+use openssl::{
+    error::ErrorStack, hash::MessageDigest, nid::Nid, pkey::PKey, sign::Signer, symm::Cipher,
+};
 
-use crate::{KdfArgument, KdfError, KdfKbMode, KdfMacType, KdfType};
+use crate::{KdfArgument, KdfError, KdfMacType, KdfType};
+
+mod argon2;
+mod blake2b;
+mod hkdf;
+mod hmac_drbg;
+mod kbkdf;
+mod kmac;
+mod krb5;
+mod pbkdf2;
+mod pkcs12;
+mod scrypt;
+mod srtp;
+mod sshkdf;
+mod sskdf;
+mod tls13;
+mod tls1_prf;
+mod x942;
+mod x963;
 
 fn get_digest_length_bytes(digest_method: MessageDigest) -> Result<usize, KdfError> {
     match digest_method.type_() {
-        Nid::SHA1 => Ok(20),
-        Nid::SHA224 => Ok(28),
-        Nid::SHA256 => Ok(32),
-        Nid::SHA384 => Ok(48),
-        Nid::SHA512 => Ok(64),
-        _ => Err(KdfError::Unimplemented("Invalid digest method")),
+        // Extendable output functions have no fixed output length to use as a PRF
+        Nid::SHAKE128 | Nid::SHAKE256 => Err(KdfError::Unimplemented("Invalid digest method")),
+        _ => match digest_method.size() {
+            0 => Err(KdfError::Unimplemented("Invalid digest method")),
+            size => Ok(size),
+        },
     }
 }
 
+// CMAC works with any block cipher, its output is a single cipher block
 fn get_cipher_length_bytes(cipher: Cipher) -> Result<usize, KdfError> {
-    match cipher.nid() {
-        Nid::AES_128_CBC => Ok(16),
-        Nid::AES_192_CBC => Ok(16),
-        Nid::AES_256_CBC => Ok(16),
-        _ => Err(KdfError::Unimplemented("Invalid cipher")),
+    match cipher.block_size() {
+        1 => Err(KdfError::Unimplemented("Invalid cipher")),
+        block_size => Ok(block_size),
+    }
+}
+
+fn hmac(md: MessageDigest, key: &[u8], input: &[&[u8]]) -> Result<Vec<u8>, ErrorStack> {
+    let hmac_key = PKey::hmac(key)?;
+    let mut signer = Signer::new(md, &hmac_key)?;
+    for part in input {
+        signer.update(part)?;
     }
+    signer.sign_to_vec()
 }
 
 pub(crate) const IMPLEMENTATION: crate::Implementation = crate::Implementation {
     supports_args: &supports_args,
     func: &perform,
+    func_bits: Some(&perform_bits),
 };
 
-fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
+fn supports_args(args: &[&KdfArgument]) -> bool {
     use crate::KdfArgument::*;
     for arg in args {
         match arg {
@@ -38,6 +67,10 @@ fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
             UseSeparator(_) => {}
             UseL(_) => {}
             LBits(_) => {}
+            CounterEndianness(_) => {}
+            LEndianness(_) => {}
+            LUnits(_) => {}
+            LPosition(_) => {}
             Mac(mac) => match mac {
                 KdfMacType::Hmac(mac) => match get_digest_length_bytes(*mac) {
                     Ok(_) => {}
@@ -47,148 +80,98 @@ fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
                     Ok(_) => {}
                     Err(_) => return false,
                 },
+                KdfMacType::Kmac128 | KdfMacType::Kmac256 => {}
             },
-            KbMode(mode) => match mode {
-                KdfKbMode::Counter => {}
-                KdfKbMode::Feedback => return false,
+            KbMode(_) => {}
+            KbSeed(_) => {}
+            UseCounter(_) => {}
+            CounterLocation(_) => {}
+            Digest(md) => match get_digest_length_bytes(*md) {
+                Ok(_) => {}
+                Err(_) => return false,
             },
-            KbSeed(_) => return false,
+            Info(_) => {}
+            HkdfMode(_) => {}
+            Password(_) => {}
+            Iterations(_) => {}
+            Pkcs5(_) => {}
+            ScryptN(_) => {}
+            ScryptR(_) => {}
+            ScryptP(_) => {}
+            MaxMemBytes(_) => {}
+            MemoryCost(_) => {}
+            Lanes(_) => {}
+            Threads(_) => {}
+            Secret(_) => {}
+            AssociatedData(_) => {}
+            SshXcghash(_) => {}
+            SshSessionId(_) => {}
+            SshType(_) => {}
+            FixedInfo(_) => {}
+            MacLen(_) => {}
+            SharedInfo(_) => {}
+            PartyUInfo(_) => {}
+            PartyVInfo(_) => {}
+            SuppPubInfo(_) => {}
+            SuppPrivInfo(_) => {}
+            UseKeyBits(_) => {}
+            CekAlg(_) => {}
+            TlsSeed(_) => {}
+            Tls13Prefix(_) => {}
+            Tls13Label(_) => {}
+            Tls13Data(_) => {}
+            Cipher(_) => {}
+            Krb5Constant(_) => {}
+            Pkcs12Id(_) => {}
+            SrtpKdr(_) => {}
+            SrtpIndex(_) => {}
+            SrtpLabel(_) => {}
+            Entropy(_) => {}
+            Nonce(_) => {}
         }
     }
 
     true
 }
 
-fn perform<'a>(
-    type_: crate::KdfType,
-    args: &[&'a KdfArgument],
-    length: usize,
-) -> Result<Vec<u8>, KdfError> {
+fn perform(type_: KdfType, args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
     #[cfg(feature = "warn_custom")]
     eprintln!("Using custom KDF");
 
-    if !matches!(type_, KdfType::KeyBased) {
-        return Err(KdfError::Unimplemented("Non-keybased KDF"));
-    }
-
-    let mut use_separator = true;
-    let mut use_l = true;
-    let mut r: u64 = 32;
-    let mut lbits: u8 = 32;
-    let mut key: Option<&'a [u8]> = None;
-    let mut label: Option<&'a [u8]> = None;
-    let mut context: Option<&'a [u8]> = None;
-    let mut h: Option<usize> = None;
-
-    let mut prf: Option<
-        Box<
-            dyn Fn(
-                &[u8],
-            ) -> Result<
-                Box<dyn Fn(&[u8]) -> Result<Vec<u8>, openssl::error::ErrorStack>>,
-                openssl::error::ErrorStack,
-            >,
-        >,
-    > = None;
-
-    for arg in args {
-        match arg {
-            KdfArgument::Key(new_key) => {
-                key = Some(new_key);
-            }
-            KdfArgument::Salt(new_salt) => {
-                label = Some(new_salt);
-            }
-            KdfArgument::KbInfo(new_kb_info) => {
-                context = Some(new_kb_info);
-            }
-            KdfArgument::R(new_r) => {
-                r = *new_r as u64;
-            }
-            KdfArgument::LBits(new_lbits) => {
-                lbits = *new_lbits;
-            }
-            KdfArgument::UseL(new_use_l) => {
-                use_l = *new_use_l;
-            }
-            KdfArgument::UseSeparator(new_use_separator) => {
-                use_separator = *new_use_separator;
-            }
-            KdfArgument::Mac(mac) => match mac {
-                KdfMacType::Hmac(md) => {
-                    h = Some(get_digest_length_bytes(*md)? * 8);
-                    prf = Some(Box::new(move |key| {
-                        let hmac_key = PKey::hmac(key)?;
-                        Ok(Box::new(move |input| {
-                            let mut signer = Signer::new(*md, &hmac_key)?;
-                            signer.update(input)?;
-                            signer.sign_to_vec()
-                        }))
-                    }));
-                }
-                KdfMacType::Cmac(cipher) => {
-                    h = Some(get_cipher_length_bytes(*cipher)? * 8);
-                    prf = Some(Box::new(move |key| {
-                        let cmac_key = PKey::cmac(cipher, key)?;
-                        Ok(Box::new(move |input| {
-                            let mut signer = Signer::new_without_digest(&cmac_key)?;
-                            signer.update(input)?;
-                            signer.sign_to_vec()
-                        }))
-                    }));
-                }
-            },
-            KdfArgument::KbMode(mode) => match mode {
-                KdfKbMode::Counter => {}
-                KdfKbMode::Feedback => {
-                    return Err(KdfError::Unimplemented("Feedback mode"));
-                }
-            },
-            KdfArgument::KbSeed(_) => {
-                return Err(KdfError::Unimplemented("KB-Seed"));
-            }
+    match type_ {
+        KdfType::KeyBased => kbkdf::perform(args, length),
+        KdfType::Hkdf => hkdf::perform(args, length),
+        KdfType::Pbkdf2 => pbkdf2::perform(args, length),
+        KdfType::Scrypt => scrypt::perform(args, length),
+        KdfType::Argon2i | KdfType::Argon2d | KdfType::Argon2id => {
+            argon2::perform(type_, args, length)
         }
+        KdfType::SshKdf => sshkdf::perform(args, length),
+        KdfType::SingleStep => sskdf::perform(args, length),
+        KdfType::X963 | KdfType::X942Concat => x963::perform(args, length),
+        KdfType::X942Asn1 => x942::perform(args, length),
+        KdfType::Tls1Prf => tls1_prf::perform(args, length),
+        KdfType::Tls13 => tls13::perform(args, length),
+        KdfType::Krb5 => krb5::perform(args, length),
+        KdfType::Pkcs12 => pkcs12::perform(args, length),
+        KdfType::Srtp | KdfType::Srtcp => srtp::perform(type_, args, length),
+        KdfType::HmacDrbg => hmac_drbg::perform(args, length),
     }
+}
 
-    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
-    let prf = prf.ok_or(KdfError::MissingArgument("Mac"))?;
-    let h = h.ok_or(KdfError::MissingArgument("h"))?;
-
-    let n = ((length * 8) as f32 / h as f32).ceil() as u64;
-
-    if n > ((2 ^ r) - 1) {
-        return Err(KdfError::InvalidOption("length too long for r"));
-    }
-    // This is the place where to start in the counter buffer (which is always be u64)
-    let start_pos: usize = 8 - (r / 8) as usize;
-
-    let lstart = ((64 - lbits) / 8) as usize;
-    let l2 = &((length * 8) as u64).to_be_bytes()[lstart..];
-
-    let mut output = Vec::new();
-
-    let prf = prf(key)?;
-
-    for i in 1..=n {
-        let mut block = Vec::new();
-
-        block.extend_from_slice(&i.to_be_bytes()[start_pos..]);
-        if let Some(label) = label {
-            block.extend_from_slice(label);
-        }
-        if use_separator {
-            block.extend_from_slice(&[00]);
-        }
-        if let Some(context) = context {
-            block.extend_from_slice(context);
-        }
-        if use_l {
-            block.extend_from_slice(l2);
-        }
+fn perform_bits(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    length_bits: usize,
+) -> Result<Vec<u8>, KdfError> {
+    #[cfg(feature = "warn_custom")]
+    eprintln!("Using custom KDF");
 
-        output.extend_from_slice(&prf(&block)?);
+    match type_ {
+        KdfType::KeyBased => kbkdf::perform_bits(args, length_bits),
+        _ => Err(KdfError::UnsupportedOption(format!(
+            "Output length in bits for {:?}",
+            type_
+        ))),
     }
-
-    output.truncate(length);
-    Ok(output)
 }
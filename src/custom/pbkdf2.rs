@@ -0,0 +1,47 @@
+use std::convert::TryFrom;
+
+use openssl::{hash::MessageDigest, pkcs5::pbkdf2_hmac};
+
+use crate::{KdfArgument, KdfError};
+
+// Defaults as used by the OpenSSL PBKDF2 implementation
+const DEFAULT_ITERATIONS: u64 = 2048;
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md = MessageDigest::sha1();
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut password: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Password(new_password) => {
+                password = Some(new_password);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::Iterations(new_iterations) => {
+                iterations = *new_iterations;
+            }
+            KdfArgument::Digest(new_md) => {
+                md = *new_md;
+            }
+            KdfArgument::Pkcs5(_) => {
+                // Handled by crate::checks
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to PBKDF2"));
+            }
+        }
+    }
+
+    let password = password.ok_or(KdfError::MissingArgument("Password"))?;
+    let salt = salt.ok_or(KdfError::MissingArgument("Salt"))?;
+    let iterations =
+        usize::try_from(iterations).map_err(|_| KdfError::InvalidOption("Iterations too large"))?;
+
+    let mut output = vec![0; length];
+    pbkdf2_hmac(password, salt, iterations, md, &mut output)?;
+    Ok(output)
+}
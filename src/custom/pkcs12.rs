@@ -0,0 +1,90 @@
+use openssl::hash::{Hasher, MessageDigest};
+
+use crate::{pkcs12::to_bmp_string, KdfArgument, KdfError};
+
+// Default as used by the OpenSSL PKCS12KDF implementation
+const DEFAULT_ITERATIONS: u64 = 2048;
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut password: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+    let mut id: Option<u8> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Password(new_password) => {
+                password = Some(new_password);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::Iterations(new_iterations) => {
+                iterations = *new_iterations;
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::Pkcs12Id(new_id) => {
+                id = Some(new_id.id());
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to PKCS12KDF",
+                ));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let password = to_bmp_string(password.ok_or(KdfError::MissingArgument("Password"))?)?;
+    let salt = salt.ok_or(KdfError::MissingArgument("Salt"))?;
+    let id = id.ok_or(KdfError::MissingArgument("Pkcs12Id"))?;
+    if iterations == 0 {
+        return Err(KdfError::InvalidOption("Iterations must not be zero"));
+    }
+
+    // RFC 7292 appendix B.2, with u the digest size and v the digest block size
+    let u = md.size();
+    let v = md.block_size();
+
+    let d = vec![id; v];
+    let mut i = Vec::new();
+    for input in [salt, &password] {
+        if !input.is_empty() {
+            let len = v * input.len().div_ceil(v);
+            i.extend(input.iter().cycle().take(len));
+        }
+    }
+
+    let mut output = Vec::with_capacity(length.div_ceil(u) * u);
+    while output.len() < length {
+        let mut hasher = Hasher::new(md)?;
+        hasher.update(&d)?;
+        hasher.update(&i)?;
+        let mut a = hasher.finish()?.to_vec();
+        for _ in 1..iterations {
+            a = openssl::hash::hash(md, &a)?.to_vec();
+        }
+        output.extend_from_slice(&a);
+
+        if output.len() >= length {
+            break;
+        }
+
+        // I_j = (I_j + B + 1) mod 2^(v * 8) for each v-byte block of I
+        let b: Vec<u8> = a.iter().cycle().take(v).copied().collect();
+        for block in i.chunks_mut(v) {
+            let mut carry: u16 = 1;
+            for (byte, b_byte) in block.iter_mut().zip(&b).rev() {
+                carry += *byte as u16 + *b_byte as u16;
+                *byte = carry as u8;
+                carry >>= 8;
+            }
+        }
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
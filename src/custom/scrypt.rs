@@ -0,0 +1,54 @@
+use openssl::pkcs5::scrypt;
+
+use crate::checks::{SCRYPT_DEFAULT_MAXMEM, SCRYPT_DEFAULT_N, SCRYPT_DEFAULT_P, SCRYPT_DEFAULT_R};
+use crate::{KdfArgument, KdfError};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut n = SCRYPT_DEFAULT_N;
+    let mut r = SCRYPT_DEFAULT_R;
+    let mut p = SCRYPT_DEFAULT_P;
+    let mut maxmem = SCRYPT_DEFAULT_MAXMEM;
+    let mut password: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Password(new_password) => {
+                password = Some(new_password);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::ScryptN(new_n) => {
+                n = *new_n;
+            }
+            KdfArgument::ScryptR(new_r) => {
+                r = *new_r;
+            }
+            KdfArgument::ScryptP(new_p) => {
+                p = *new_p;
+            }
+            KdfArgument::MaxMemBytes(new_maxmem) => {
+                maxmem = *new_maxmem;
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to scrypt"));
+            }
+        }
+    }
+
+    let password = password.ok_or(KdfError::MissingArgument("Password"))?;
+    let salt = salt.ok_or(KdfError::MissingArgument("Salt"))?;
+
+    let mut output = vec![0; length];
+    scrypt(
+        password,
+        salt,
+        n,
+        u64::from(r),
+        u64::from(p),
+        maxmem,
+        &mut output,
+    )?;
+    Ok(output)
+}
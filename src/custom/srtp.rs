@@ -0,0 +1,96 @@
+use openssl::symm::{encrypt, Cipher};
+
+use crate::{KdfArgument, KdfError, KdfSrtpLabel, KdfType};
+
+const MASTER_SALT_BYTES: usize = 14;
+// The key_id is the label byte followed by the 48-bit r
+const KEY_ID_BYTES: usize = 7;
+
+pub(super) fn perform(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    length: usize,
+) -> Result<Vec<u8>, KdfError> {
+    let mut cipher: Option<Cipher> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+    let mut kdr: u32 = 0;
+    let mut index: Option<&[u8]> = None;
+    let mut label: Option<KdfSrtpLabel> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Cipher(new_cipher) => {
+                cipher = Some(*new_cipher);
+            }
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::SrtpKdr(new_kdr) => {
+                kdr = *new_kdr;
+            }
+            KdfArgument::SrtpIndex(new_index) => {
+                index = Some(new_index);
+            }
+            KdfArgument::SrtpLabel(new_label) => {
+                label = Some(*new_label);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to SRTPKDF",
+                ));
+            }
+        }
+    }
+
+    let cipher = cipher.ok_or(KdfError::MissingArgument("Cipher"))?;
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let salt = salt.ok_or(KdfError::MissingArgument("Salt"))?;
+    let label = label.ok_or(KdfError::MissingArgument("SrtpLabel"))?;
+
+    let aes_ctr = [
+        Cipher::aes_128_ctr(),
+        Cipher::aes_192_ctr(),
+        Cipher::aes_256_ctr(),
+    ];
+    if !aes_ctr.iter().any(|c| c.nid() == cipher.nid()) {
+        return Err(KdfError::Unimplemented(
+            "SRTPKDF requires an AES-CTR cipher",
+        ));
+    }
+    if key.len() != cipher.key_len() {
+        return Err(KdfError::InvalidOption("Key length does not match cipher"));
+    }
+    if salt.len() != MASTER_SALT_BYTES {
+        return Err(KdfError::InvalidOption("Salt must be 14 bytes"));
+    }
+
+    // r = index DIV kdr, where a DIV 0 = 0
+    let r = if kdr == 0 {
+        0
+    } else {
+        let index = index.ok_or(KdfError::MissingArgument("SrtpIndex"))?;
+        let index = index.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        index / u64::from(kdr)
+    };
+
+    let mut key_id = [0u8; KEY_ID_BYTES];
+    key_id[0] = label.label(type_);
+    key_id[1..].copy_from_slice(&r.to_be_bytes()[2..]);
+
+    // x = key_id XOR master_salt, right-aligned, and the AES-CM IV is x * 2^16
+    let mut iv = [0u8; 16];
+    iv[..MASTER_SALT_BYTES].copy_from_slice(salt);
+    for (x, k) in iv[MASTER_SALT_BYTES - KEY_ID_BYTES..MASTER_SALT_BYTES]
+        .iter_mut()
+        .zip(key_id.iter())
+    {
+        *x ^= k;
+    }
+
+    // The PRF output is the AES-CM keystream, so encrypt zeroes
+    Ok(encrypt(cipher, key, Some(&iv), &vec![0; length])?)
+}
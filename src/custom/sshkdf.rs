@@ -0,0 +1,59 @@
+use openssl::hash::{Hasher, MessageDigest};
+
+use crate::{KdfArgument, KdfError, KdfSshType};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut xcghash: Option<&[u8]> = None;
+    let mut session_id: Option<&[u8]> = None;
+    let mut type_: Option<KdfSshType> = None;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::SshXcghash(new_xcghash) => {
+                xcghash = Some(new_xcghash);
+            }
+            KdfArgument::SshSessionId(new_session_id) => {
+                session_id = Some(new_session_id);
+            }
+            KdfArgument::SshType(new_type) => {
+                type_ = Some(*new_type);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to SSHKDF"));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let xcghash = xcghash.ok_or(KdfError::MissingArgument("SshXcghash"))?;
+    let session_id = session_id.ok_or(KdfError::MissingArgument("SshSessionId"))?;
+    let type_ = type_.ok_or(KdfError::MissingArgument("SshType"))?;
+
+    // K1 = HASH(K || H || X || session_id), Kn = HASH(K || H || K1 || ... || Kn-1)
+    let mut hasher = Hasher::new(md)?;
+    hasher.update(key)?;
+    hasher.update(xcghash)?;
+    hasher.update(&[type_.letter()])?;
+    hasher.update(session_id)?;
+    let mut output = hasher.finish()?.to_vec();
+
+    while output.len() < length {
+        let mut hasher = Hasher::new(md)?;
+        hasher.update(key)?;
+        hasher.update(xcghash)?;
+        hasher.update(&output)?;
+        output.extend_from_slice(&hasher.finish()?);
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
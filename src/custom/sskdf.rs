@@ -0,0 +1,124 @@
+use openssl::hash::{Hasher, MessageDigest};
+
+use super::{
+    hmac,
+    kmac::{kmac, Kmac},
+};
+use crate::{KdfArgument, KdfError, KdfMacType};
+
+// The auxiliary function H from SP800-56C rev2 section 4.1
+enum Auxiliary<'a> {
+    Hash(MessageDigest),
+    Hmac(MessageDigest, Option<&'a [u8]>),
+    Kmac(Kmac, Option<&'a [u8]>),
+}
+
+// SP800-56C rev2 section 4.1 option 3
+const KMAC_CUSTOM: &[u8] = b"KDF";
+
+pub(super) fn perform<'a>(args: &[&'a KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut mac: Option<MessageDigest> = None;
+    let mut kmac_variant: Option<Kmac> = None;
+    let mut maclen: Option<usize> = None;
+    let mut key: Option<&'a [u8]> = None;
+    let mut salt: Option<&'a [u8]> = None;
+    let mut fixed_info: Vec<u8> = Vec::new();
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::FixedInfo(new_fixed_info) => {
+                fixed_info.extend_from_slice(new_fixed_info);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::Mac(new_mac) => match new_mac {
+                KdfMacType::Hmac(new_md) => {
+                    mac = Some(*new_md);
+                    kmac_variant = None;
+                }
+                KdfMacType::Cmac(_) => {
+                    return Err(KdfError::InvalidOption(
+                        "CMAC is not an SSKDF auxiliary function",
+                    ));
+                }
+                KdfMacType::Kmac128 => {
+                    mac = None;
+                    kmac_variant = Some(Kmac::Kmac128);
+                }
+                KdfMacType::Kmac256 => {
+                    mac = None;
+                    kmac_variant = Some(Kmac::Kmac256);
+                }
+            },
+            KdfArgument::MacLen(new_maclen) => {
+                // Only used for KMAC, the HMAC output size is fixed by the digest
+                maclen = Some(*new_maclen);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to SSKDF"));
+            }
+        }
+    }
+
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let aux = match (kmac_variant, mac, md) {
+        (Some(variant), _, _) => Auxiliary::Kmac(variant, salt),
+        (None, Some(mac), _) => Auxiliary::Hmac(mac, salt),
+        (None, None, Some(md)) => Auxiliary::Hash(md),
+        (None, None, None) => return Err(KdfError::MissingArgument("Digest")),
+    };
+    // As in OpenSSL, the KMAC output size defaults to the requested length
+    let maclen = maclen.unwrap_or(length);
+    if matches!(aux, Auxiliary::Kmac(..)) && maclen == 0 {
+        return Err(KdfError::InvalidOption("MacLen must not be zero"));
+    }
+
+    let mut output = Vec::with_capacity(length);
+    let mut counter: u32 = 1;
+
+    while output.len() < length {
+        let counter_bytes = counter.to_be_bytes();
+        let block = match aux {
+            Auxiliary::Hash(md) => {
+                let mut hasher = Hasher::new(md)?;
+                hasher.update(&counter_bytes)?;
+                hasher.update(key)?;
+                hasher.update(&fixed_info)?;
+                hasher.finish()?.to_vec()
+            }
+            Auxiliary::Hmac(md, salt) => {
+                // The default salt is all-zero with the length of the hash input block
+                let default_salt = vec![0; md.block_size()];
+                let salt = salt.unwrap_or(&default_salt);
+                hmac(md, salt, &[&counter_bytes, key, &fixed_info])?
+            }
+            Auxiliary::Kmac(variant, salt) => {
+                let default_salt = vec![0; variant.default_key_len()];
+                let salt = salt.unwrap_or(&default_salt);
+                kmac(
+                    variant,
+                    salt,
+                    &[&counter_bytes, key, &fixed_info],
+                    maclen,
+                    KMAC_CUSTOM,
+                )
+            }
+        };
+        output.extend_from_slice(&block);
+
+        counter = counter
+            .checked_add(1)
+            .ok_or(KdfError::InvalidOption("length too long for SSKDF"))?;
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
@@ -0,0 +1,108 @@
+use std::convert::TryFrom;
+
+use openssl::hash::{hash, MessageDigest};
+
+use super::{get_digest_length_bytes, hkdf};
+use crate::{KdfArgument, KdfError, KdfHkdfMode};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut mode = KdfHkdfMode::ExtractAndExpand;
+    let mut md: Option<MessageDigest> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut salt: Option<&[u8]> = None;
+    let mut prefix: &[u8] = &[];
+    let mut label: &[u8] = &[];
+    let mut data: &[u8] = &[];
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Salt(new_salt) => {
+                salt = Some(new_salt);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::HkdfMode(new_mode) => {
+                mode = *new_mode;
+            }
+            KdfArgument::Tls13Prefix(new_prefix) => {
+                prefix = new_prefix;
+            }
+            KdfArgument::Tls13Label(new_label) => {
+                label = new_label;
+            }
+            KdfArgument::Tls13Data(new_data) => {
+                data = new_data;
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to TLS13-KDF",
+                ));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let h = get_digest_length_bytes(md)?;
+
+    match mode {
+        KdfHkdfMode::ExtractOnly => {
+            if length != h {
+                return Err(KdfError::InvalidOption(
+                    "length must equal digest size for extract-only",
+                ));
+            }
+            // RFC 8446 section 7.1: a missing input secret is replaced by HashLen zeros,
+            // and a previous secret is first passed through Derive-Secret(., "derived", "")
+            let zero_key = vec![0; h];
+            let key = key.unwrap_or(&zero_key);
+            match salt {
+                Some(salt) => {
+                    let empty_hash = hash(md, &[])?;
+                    let salt = expand_label(md, h, salt, prefix, label, &empty_hash, h)?;
+                    hkdf::extract(md, h, Some(&salt), key)
+                }
+                None => hkdf::extract(md, h, None, key),
+            }
+        }
+        KdfHkdfMode::ExpandOnly => {
+            let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+            expand_label(md, h, key, prefix, label, data, length)
+        }
+        KdfHkdfMode::ExtractAndExpand => Err(KdfError::InvalidOption(
+            "TLS13-KDF only supports extract-only and expand-only modes",
+        )),
+    }
+}
+
+// HKDF-Expand-Label from RFC 8446 section 7.1, with a configurable prefix for DTLS 1.3
+fn expand_label(
+    md: MessageDigest,
+    h: usize,
+    secret: &[u8],
+    prefix: &[u8],
+    label: &[u8],
+    context: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, KdfError> {
+    let length_bytes = u16::try_from(length)
+        .map_err(|_| KdfError::InvalidOption("length too long for TLS13-KDF"))?
+        .to_be_bytes();
+    let label_len = u8::try_from(prefix.len() + label.len())
+        .map_err(|_| KdfError::InvalidOption("TLS13-KDF label too long"))?;
+    let context_len = u8::try_from(context.len())
+        .map_err(|_| KdfError::InvalidOption("TLS13-KDF data too long"))?;
+
+    let mut hkdf_label = Vec::new();
+    hkdf_label.extend_from_slice(&length_bytes);
+    hkdf_label.push(label_len);
+    hkdf_label.extend_from_slice(prefix);
+    hkdf_label.extend_from_slice(label);
+    hkdf_label.push(context_len);
+    hkdf_label.extend_from_slice(context);
+
+    hkdf::expand(md, h, secret, &hkdf_label, length)
+}
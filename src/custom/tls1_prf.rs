@@ -0,0 +1,73 @@
+use openssl::{error::ErrorStack, hash::MessageDigest, nid::Nid};
+
+use super::hmac;
+use crate::{KdfArgument, KdfError};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut secret: Option<&[u8]> = None;
+    let mut seed: Vec<u8> = Vec::new();
+
+    for arg in args {
+        match arg {
+            KdfArgument::Secret(new_secret) => {
+                secret = Some(new_secret);
+            }
+            KdfArgument::TlsSeed(new_seed) => {
+                seed.extend_from_slice(new_seed);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption(
+                    "Argument not applicable to TLS1-PRF",
+                ));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let secret = secret.ok_or(KdfError::MissingArgument("Secret"))?;
+    if seed.is_empty() {
+        return Err(KdfError::MissingArgument("TlsSeed"));
+    }
+
+    if md.type_() != Nid::MD5_SHA1 {
+        return Ok(p_hash(md, secret, &seed, length)?);
+    }
+
+    // TLS 1.0 and 1.1 split the secret into two (possibly overlapping) halves,
+    // PRF = P_MD5(S1, seed) XOR P_SHA-1(S2, seed)
+    let half = secret.len().div_ceil(2);
+    let mut output = p_hash(MessageDigest::md5(), &secret[..half], &seed, length)?;
+    let sha1_output = p_hash(
+        MessageDigest::sha1(),
+        &secret[secret.len() - half..],
+        &seed,
+        length,
+    )?;
+    for (out, sha1_out) in output.iter_mut().zip(sha1_output) {
+        *out ^= sha1_out;
+    }
+    Ok(output)
+}
+
+// P_hash from RFC 5246 section 5
+fn p_hash(
+    md: MessageDigest,
+    secret: &[u8],
+    seed: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut output = Vec::with_capacity(length);
+    let mut a = hmac(md, secret, &[seed])?;
+
+    while output.len() < length {
+        output.extend_from_slice(&hmac(md, secret, &[&a, seed])?);
+        a = hmac(md, secret, &[&a])?;
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
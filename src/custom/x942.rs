@@ -0,0 +1,163 @@
+use openssl::{
+    hash::{Hasher, MessageDigest},
+    nid::Nid,
+};
+
+use crate::{KdfArgument, KdfCekAlg, KdfError};
+
+// Key wrap algorithms accepted as "cekalg", as in the OpenSSL X9.42 implementation
+struct KekAlg {
+    names: &'static [&'static str],
+    nid: Nid,
+    oid: &'static [u8],
+    key_len: usize,
+}
+
+const KEK_ALGS: &[KekAlg] = &[
+    KekAlg {
+        names: &["id-smime-alg-CMS3DESwrap", "DES3-WRAP", "des3-wrap"],
+        nid: Nid::ID_SMIME_ALG_CMS3DESWRAP,
+        oid: &[
+            0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x03, 0x06,
+        ],
+        key_len: 24,
+    },
+    KekAlg {
+        names: &["id-aes128-wrap", "AES-128-WRAP", "AES128-WRAP"],
+        nid: Nid::ID_AES128_WRAP,
+        oid: &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x05],
+        key_len: 16,
+    },
+    KekAlg {
+        names: &["id-aes192-wrap", "AES-192-WRAP", "AES192-WRAP"],
+        nid: Nid::ID_AES192_WRAP,
+        oid: &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x19],
+        key_len: 24,
+    },
+    KekAlg {
+        names: &["id-aes256-wrap", "AES-256-WRAP", "AES256-WRAP"],
+        nid: Nid::ID_AES256_WRAP,
+        oid: &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2d],
+        key_len: 32,
+    },
+];
+
+fn find_kek_alg(cek_alg: &KdfCekAlg) -> Option<&'static KekAlg> {
+    KEK_ALGS.iter().find(|kek_alg| match cek_alg {
+        KdfCekAlg::Cipher(cipher) => cipher.nid() == kek_alg.nid,
+        KdfCekAlg::Name(name) => kek_alg
+            .names
+            .iter()
+            .any(|kek_name| kek_name.eq_ignore_ascii_case(name)),
+    })
+}
+
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut output = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        output.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len_bytes.iter().take_while(|b| **b == 0).count();
+        output.push(0x80 | (len_bytes.len() - skip) as u8);
+        output.extend_from_slice(&len_bytes[skip..]);
+    }
+    output.extend_from_slice(content);
+    output
+}
+
+fn der_explicit_octet_string(tag: u8, content: &[u8]) -> Vec<u8> {
+    der_tlv(0xa0 | tag, &der_tlv(0x04, content))
+}
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut cek_alg: Option<&'static KekAlg> = None;
+    let mut party_u_info: Option<&[u8]> = None;
+    let mut party_v_info: Option<&[u8]> = None;
+    let mut supp_pub_info: Option<&[u8]> = None;
+    let mut supp_priv_info: Option<&[u8]> = None;
+    let mut use_keybits = true;
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            KdfArgument::CekAlg(new_cek_alg) => {
+                cek_alg = Some(
+                    find_kek_alg(new_cek_alg)
+                        .ok_or(KdfError::InvalidOption("Unsupported CEK algorithm"))?,
+                );
+            }
+            KdfArgument::PartyUInfo(info) => {
+                party_u_info = Some(info);
+            }
+            KdfArgument::PartyVInfo(info) => {
+                party_v_info = Some(info);
+            }
+            KdfArgument::SuppPubInfo(info) => {
+                supp_pub_info = Some(info);
+            }
+            KdfArgument::SuppPrivInfo(info) => {
+                supp_priv_info = Some(info);
+            }
+            KdfArgument::UseKeyBits(new_use_keybits) => {
+                use_keybits = *new_use_keybits;
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to X9.42"));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+    let cek_alg = cek_alg.ok_or(KdfError::MissingArgument("CekAlg"))?;
+
+    // With UseKeyBits, suppPubInfo holds the length of the CEK in bits
+    let keybits;
+    if use_keybits {
+        if supp_pub_info.is_some() {
+            return Err(KdfError::InvalidOption(
+                "SuppPubInfo cannot be combined with UseKeyBits",
+            ));
+        }
+        keybits = ((cek_alg.key_len * 8) as u32).to_be_bytes();
+        supp_pub_info = Some(&keybits);
+    }
+
+    let mut output = Vec::with_capacity(length);
+    let mut counter: u32 = 1;
+
+    // K(i) = H(ZZ || OtherInfo), with the counter in the KeySpecificInfo of OtherInfo
+    while output.len() < length {
+        let mut key_info = der_tlv(0x06, cek_alg.oid);
+        key_info.extend_from_slice(&der_tlv(0x04, &counter.to_be_bytes()));
+
+        let mut other_info = der_tlv(0x30, &key_info);
+        let optional_infos = [party_u_info, party_v_info, supp_pub_info, supp_priv_info];
+        for (tag, info) in optional_infos.iter().enumerate() {
+            if let Some(info) = info {
+                other_info.extend_from_slice(&der_explicit_octet_string(tag as u8, info));
+            }
+        }
+
+        let mut hasher = Hasher::new(md)?;
+        hasher.update(key)?;
+        hasher.update(&der_tlv(0x30, &other_info))?;
+        output.extend_from_slice(&hasher.finish()?);
+
+        counter = counter
+            .checked_add(1)
+            .ok_or(KdfError::InvalidOption("length too long for X9.42"))?;
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
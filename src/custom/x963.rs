@@ -0,0 +1,48 @@
+use openssl::hash::{Hasher, MessageDigest};
+
+use crate::{KdfArgument, KdfError};
+
+pub(super) fn perform(args: &[&KdfArgument], length: usize) -> Result<Vec<u8>, KdfError> {
+    let mut md: Option<MessageDigest> = None;
+    let mut key: Option<&[u8]> = None;
+    let mut shared_info: Vec<u8> = Vec::new();
+
+    for arg in args {
+        match arg {
+            KdfArgument::Key(new_key) => {
+                key = Some(new_key);
+            }
+            KdfArgument::SharedInfo(new_shared_info) => {
+                shared_info.extend_from_slice(new_shared_info);
+            }
+            KdfArgument::Digest(new_md) => {
+                md = Some(*new_md);
+            }
+            _ => {
+                return Err(KdfError::InvalidOption("Argument not applicable to X9.63"));
+            }
+        }
+    }
+
+    let md = md.ok_or(KdfError::MissingArgument("Digest"))?;
+    let key = key.ok_or(KdfError::MissingArgument("Key"))?;
+
+    let mut output = Vec::with_capacity(length);
+    let mut counter: u32 = 1;
+
+    // K(i) = Hash(Z || Counter || SharedInfo)
+    while output.len() < length {
+        let mut hasher = Hasher::new(md)?;
+        hasher.update(key)?;
+        hasher.update(&counter.to_be_bytes())?;
+        hasher.update(&shared_info)?;
+        output.extend_from_slice(&hasher.finish()?);
+
+        counter = counter
+            .checked_add(1)
+            .ok_or(KdfError::InvalidOption("length too long for X9.63"))?;
+    }
+
+    output.truncate(length);
+    Ok(output)
+}
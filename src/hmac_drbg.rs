@@ -0,0 +1,61 @@
+use openssl::{error::ErrorStack, hash::MessageDigest, pkey::PKey, sign::Signer};
+
+// HMAC_DRBG from SP800-90A section 10.1.2, without reseeding or additional input
+pub(crate) struct HmacDrbg {
+    md: MessageDigest,
+    k: Vec<u8>,
+    v: Vec<u8>,
+}
+
+impl HmacDrbg {
+    pub(crate) fn new(
+        md: MessageDigest,
+        entropy: &[u8],
+        nonce: &[u8],
+    ) -> Result<HmacDrbg, ErrorStack> {
+        let mut drbg = HmacDrbg {
+            md,
+            k: vec![0x00; md.size()],
+            v: vec![0x01; md.size()],
+        };
+        drbg.update(&[entropy, nonce])?;
+        Ok(drbg)
+    }
+
+    fn hmac(&self, input: &[&[u8]]) -> Result<Vec<u8>, ErrorStack> {
+        let key = PKey::hmac(&self.k)?;
+        let mut signer = Signer::new(self.md, &key)?;
+        for part in input {
+            signer.update(part)?;
+        }
+        signer.sign_to_vec()
+    }
+
+    fn update(&mut self, provided_data: &[&[u8]]) -> Result<(), ErrorStack> {
+        let has_data = provided_data.iter().any(|part| !part.is_empty());
+
+        for separator in [[0x00u8], [0x01]] {
+            let mut input: Vec<&[u8]> = vec![&self.v, &separator];
+            input.extend_from_slice(provided_data);
+            self.k = self.hmac(&input)?;
+            self.v = self.hmac(&[&self.v])?;
+
+            if !has_data {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn generate(&mut self, length: usize) -> Result<Vec<u8>, ErrorStack> {
+        let mut output = Vec::with_capacity(length + self.v.len());
+        while output.len() < length {
+            self.v = self.hmac(&[&self.v])?;
+            output.extend_from_slice(&self.v);
+        }
+        output.truncate(length);
+
+        self.update(&[])?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    checks, perform_kdf, perform_kdf_bits, perform_kdf_split, KdfArgument, KdfCounterLocation,
+    KdfEndianness, KdfError, KdfKbMode, KdfLPosition, KdfLUnits, KdfMacType, KdfType,
+};
+
+/// A validated SP800-108 KBKDF configuration, created through `Kbkdf::builder`.
+///
+/// The same configuration can be used to derive several outputs of different lengths.
+#[derive(Clone)]
+pub struct Kbkdf<'a> {
+    args: Vec<KdfArgument<'a>>,
+}
+
+/// Builder for `Kbkdf`, the PRF and key are required and taken by `Kbkdf::builder`.
+///
+/// Options that are not set are left to the backend defaults, which are those of OpenSSL.
+#[derive(Clone)]
+pub struct KbkdfBuilder<'a> {
+    mac: KdfMacType,
+    key: &'a [u8],
+    mode: KdfKbMode,
+    label: Option<&'a [u8]>,
+    context: Option<&'a [u8]>,
+    seed: Option<&'a [u8]>,
+    r: Option<u8>,
+    use_counter: Option<bool>,
+    counter_location: Option<KdfCounterLocation>,
+    counter_endianness: Option<KdfEndianness>,
+    use_separator: Option<bool>,
+    use_l: Option<bool>,
+    l_bits: Option<u8>,
+    l_endianness: Option<KdfEndianness>,
+    l_units: Option<KdfLUnits>,
+    l_position: Option<KdfLPosition>,
+}
+
+impl<'a> Kbkdf<'a> {
+    /// Starts a counter mode configuration with the given PRF and key derivation key.
+    pub fn builder(mac: KdfMacType, key: &'a [u8]) -> KbkdfBuilder<'a> {
+        KbkdfBuilder {
+            mac,
+            key,
+            mode: KdfKbMode::Counter,
+            label: None,
+            context: None,
+            seed: None,
+            r: None,
+            use_counter: None,
+            counter_location: None,
+            counter_endianness: None,
+            use_separator: None,
+            use_l: None,
+            l_bits: None,
+            l_endianness: None,
+            l_units: None,
+            l_position: None,
+        }
+    }
+
+    fn args(&self) -> Vec<&KdfArgument<'a>> {
+        self.args.iter().collect()
+    }
+
+    /// Derives `length` bytes of output.
+    pub fn derive(&self, length: usize) -> Result<Vec<u8>, KdfError> {
+        perform_kdf(KdfType::KeyBased, &self.args(), length)
+    }
+
+    /// Derives `length_bits` bits of output, see `perform_kdf_bits`.
+    pub fn derive_bits(&self, length_bits: usize) -> Result<Vec<u8>, KdfError> {
+        perform_kdf_bits(KdfType::KeyBased, &self.args(), length_bits)
+    }
+
+    /// Derives one buffer per length from a single invocation, see `perform_kdf_split`.
+    pub fn derive_split(&self, lengths: &[usize]) -> Result<Vec<Vec<u8>>, KdfError> {
+        perform_kdf_split(KdfType::KeyBased, &self.args(), lengths)
+    }
+}
+
+impl<'a> KbkdfBuilder<'a> {
+    pub fn mode(mut self, mode: KdfKbMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The SP800-108 Label.
+    pub fn label(mut self, label: &'a [u8]) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// The SP800-108 Context.
+    pub fn context(mut self, context: &'a [u8]) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// The IV of feedback mode.
+    pub fn seed(mut self, seed: &'a [u8]) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The width of the counter in bits.
+    pub fn r(mut self, r: u8) -> Self {
+        self.r = Some(r);
+        self
+    }
+
+    pub fn use_counter(mut self, use_counter: bool) -> Self {
+        self.use_counter = Some(use_counter);
+        self
+    }
+
+    pub fn counter_location(mut self, counter_location: KdfCounterLocation) -> Self {
+        self.counter_location = Some(counter_location);
+        self
+    }
+
+    pub fn counter_endianness(mut self, endianness: KdfEndianness) -> Self {
+        self.counter_endianness = Some(endianness);
+        self
+    }
+
+    /// Whether a zero byte separates the Label and the Context.
+    pub fn use_separator(mut self, use_separator: bool) -> Self {
+        self.use_separator = Some(use_separator);
+        self
+    }
+
+    /// Whether the fixed input data includes the output length L.
+    pub fn use_l(mut self, use_l: bool) -> Self {
+        self.use_l = Some(use_l);
+        self
+    }
+
+    /// The width of the L field in bits.
+    pub fn l_bits(mut self, l_bits: u8) -> Self {
+        self.l_bits = Some(l_bits);
+        self
+    }
+
+    pub fn l_endianness(mut self, endianness: KdfEndianness) -> Self {
+        self.l_endianness = Some(endianness);
+        self
+    }
+
+    pub fn l_units(mut self, l_units: KdfLUnits) -> Self {
+        self.l_units = Some(l_units);
+        self
+    }
+
+    pub fn l_position(mut self, l_position: KdfLPosition) -> Self {
+        self.l_position = Some(l_position);
+        self
+    }
+
+    /// Checks the combination of options and returns the configuration.
+    pub fn build(self) -> Result<Kbkdf<'a>, KdfError> {
+        self.validate()?;
+
+        let mut args = vec![
+            KdfArgument::KbMode(self.mode),
+            KdfArgument::Mac(self.mac),
+            KdfArgument::Key(self.key),
+        ];
+        args.extend(self.label.map(KdfArgument::Salt));
+        args.extend(self.context.map(KdfArgument::KbInfo));
+        args.extend(self.seed.map(KdfArgument::KbSeed));
+        args.extend(self.r.map(KdfArgument::R));
+        args.extend(self.use_counter.map(KdfArgument::UseCounter));
+        args.extend(self.counter_location.map(KdfArgument::CounterLocation));
+        args.extend(self.counter_endianness.map(KdfArgument::CounterEndianness));
+        args.extend(self.use_separator.map(KdfArgument::UseSeparator));
+        args.extend(self.use_l.map(KdfArgument::UseL));
+        args.extend(self.l_bits.map(KdfArgument::LBits));
+        args.extend(self.l_endianness.map(KdfArgument::LEndianness));
+        args.extend(self.l_units.map(KdfArgument::LUnits));
+        args.extend(self.l_position.map(KdfArgument::LPosition));
+
+        Ok(Kbkdf { args })
+    }
+
+    fn validate(&self) -> Result<(), KdfError> {
+        let is_kmac = matches!(self.mac, KdfMacType::Kmac128 | KdfMacType::Kmac256);
+        let counter_options = self.r.is_some()
+            || self.use_counter.is_some()
+            || self.counter_location.is_some()
+            || self.counter_endianness.is_some();
+        let l_options = self.use_separator.is_some()
+            || self.use_l.is_some()
+            || self.l_bits.is_some()
+            || self.l_endianness.is_some()
+            || self.l_units.is_some()
+            || self.l_position.is_some();
+
+        if is_kmac && !matches!(self.mode, KdfKbMode::Counter) {
+            return Err(KdfError::InvalidOption(
+                "KMAC is only defined in counter mode",
+            ));
+        }
+        if is_kmac && (counter_options || l_options) {
+            return Err(KdfError::InvalidOption(
+                "KMAC does not use the counter or the fixed input encoding options",
+            ));
+        }
+        if self.seed.is_some() && !matches!(self.mode, KdfKbMode::Feedback) {
+            return Err(KdfError::InvalidOption(
+                "Seed is only used in feedback mode",
+            ));
+        }
+        if matches!(self.counter_location, Some(KdfCounterLocation::BeforeIter))
+            && matches!(self.mode, KdfKbMode::Counter)
+        {
+            return Err(KdfError::InvalidOption(
+                "Counter mode has no iteration variable to place the counter before",
+            ));
+        }
+        if self.use_counter == Some(false) {
+            if matches!(self.mode, KdfKbMode::Counter) {
+                return Err(KdfError::InvalidOption("Counter mode requires the counter"));
+            }
+            if self.r.is_some()
+                || self.counter_location.is_some()
+                || self.counter_endianness.is_some()
+            {
+                return Err(KdfError::InvalidOption(
+                    "Counter options set without the counter",
+                ));
+            }
+        }
+        if self.use_l == Some(false)
+            && (self.l_bits.is_some()
+                || self.l_endianness.is_some()
+                || self.l_units.is_some()
+                || self.l_position.is_some())
+        {
+            return Err(KdfError::InvalidOption("L options set without L"));
+        }
+        if let Some(r) = self.r {
+            checks::check_kbkdf_r(r)?;
+        }
+        if let Some(l_bits) = self.l_bits {
+            checks::check_kbkdf_lbits(l_bits)?;
+        }
+
+        Ok(())
+    }
+}
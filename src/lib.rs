@@ -13,6 +13,12 @@ pub enum KdfError {
     MissingArgument(&'static str),
     #[error("Invalid option provided: {0}")]
     InvalidOption(&'static str),
+    #[error("Memory limit exceeded: {0} bytes required, {1} bytes allowed")]
+    MemoryLimitExceeded(u64, u64),
+    #[error("Output length exceeded: {0} bits requested, {1} bits allowed")]
+    OutputTooLong(u64, u64),
+    #[error("Output length {0} does not fit in an L of {1} bits")]
+    LengthNotEncodable(u64, u8),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -20,6 +26,124 @@ pub enum KdfError {
 pub enum KdfKbMode {
     Counter,
     Feedback,
+    // SP800-108 section 5.3, where the iteration variable is A(i) = PRF(KI, A(i-1))
+    DoublePipeline,
+}
+
+// Where the SP800-108 counter goes relative to the fixed input data, which follows the
+// iteration variable in feedback and double-pipeline mode
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfCounterLocation {
+    BeforeFixed,
+    AfterFixed,
+    // The counter is inserted at this byte offset into the fixed input data
+    MiddleFixed(usize),
+    // The counter precedes the iteration variable, so not in counter mode
+    BeforeIter,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfEndianness {
+    Big,
+    Little,
+}
+
+// Whether the SP800-108 L field encodes the output length in bits or in bytes
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfLUnits {
+    Bits,
+    Bytes,
+}
+
+// Where the L field goes in the fixed input data, the separator stays after the label
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfLPosition {
+    AfterContext,
+    BeforeContext,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfHkdfMode {
+    ExtractAndExpand,
+    ExtractOnly,
+    ExpandOnly,
+}
+
+// The single-letter derivation types from RFC 4253 section 7.2
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfSshType {
+    InitialIvClientToServer,
+    InitialIvServerToClient,
+    EncryptionKeyClientToServer,
+    EncryptionKeyServerToClient,
+    IntegrityKeyClientToServer,
+    IntegrityKeyServerToClient,
+}
+
+impl KdfSshType {
+    #[allow(unused)]
+    fn letter(&self) -> u8 {
+        match self {
+            KdfSshType::InitialIvClientToServer => b'A',
+            KdfSshType::InitialIvServerToClient => b'B',
+            KdfSshType::EncryptionKeyClientToServer => b'C',
+            KdfSshType::EncryptionKeyServerToClient => b'D',
+            KdfSshType::IntegrityKeyClientToServer => b'E',
+            KdfSshType::IntegrityKeyServerToClient => b'F',
+        }
+    }
+}
+
+// The purpose of the derived material, the ID byte from RFC 7292 appendix B.3
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfPkcs12Id {
+    Key,
+    Iv,
+    Mac,
+}
+
+impl KdfPkcs12Id {
+    #[allow(unused)]
+    fn id(&self) -> u8 {
+        match self {
+            KdfPkcs12Id::Key => 1,
+            KdfPkcs12Id::Iv => 2,
+            KdfPkcs12Id::Mac => 3,
+        }
+    }
+}
+
+// The purpose of the derived material, SRTCP uses the labels following those of SRTP
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfSrtpLabel {
+    EncryptionKey,
+    AuthenticationKey,
+    SaltingKey,
+}
+
+impl KdfSrtpLabel {
+    #[allow(unused)]
+    fn label(&self, type_: KdfType) -> u8 {
+        let offset = if matches!(type_, KdfType::Srtcp) {
+            3
+        } else {
+            0
+        };
+        offset
+            + match self {
+                KdfSrtpLabel::EncryptionKey => 0,
+                KdfSrtpLabel::AuthenticationKey => 1,
+                KdfSrtpLabel::SaltingKey => 2,
+            }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -27,6 +151,8 @@ pub enum KdfKbMode {
 pub enum KdfMacType {
     Hmac(openssl::hash::MessageDigest),
     Cmac(openssl::symm::Cipher),
+    Kmac128,
+    Kmac256,
 }
 
 impl KdfMacType {
@@ -39,7 +165,7 @@ impl KdfMacType {
     fn get_md(&self) -> Option<&openssl::hash::MessageDigest> {
         match self {
             KdfMacType::Hmac(md) => Some(md),
-            KdfMacType::Cmac(_) => None,
+            KdfMacType::Cmac(_) | KdfMacType::Kmac128 | KdfMacType::Kmac256 => None,
         }
     }
 
@@ -52,7 +178,7 @@ impl KdfMacType {
     fn get_cipher(&self) -> Option<&openssl::symm::Cipher> {
         match self {
             KdfMacType::Cmac(cipher) => Some(cipher),
-            KdfMacType::Hmac(_) => None,
+            KdfMacType::Hmac(_) | KdfMacType::Kmac128 | KdfMacType::Kmac256 => None,
         }
     }
 }
@@ -62,6 +188,25 @@ impl std::fmt::Debug for KdfMacType {
         match self {
             KdfMacType::Hmac(md) => write!(f, "Hmac({:?})", md.type_().long_name()),
             KdfMacType::Cmac(cipher) => write!(f, "Cmac({:?})", cipher.nid().long_name()),
+            KdfMacType::Kmac128 => write!(f, "Kmac128"),
+            KdfMacType::Kmac256 => write!(f, "Kmac256"),
+        }
+    }
+}
+
+// The key wrap algorithm of the content-encryption key, called "cekalg" in OpenSSL
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub enum KdfCekAlg<'a> {
+    Cipher(openssl::symm::Cipher),
+    Name(&'a str),
+}
+
+impl std::fmt::Debug for KdfCekAlg<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KdfCekAlg::Cipher(cipher) => write!(f, "Cipher({:?})", cipher.nid().long_name()),
+            KdfCekAlg::Name(name) => f.debug_tuple("Name").field(name).finish(),
         }
     }
 }
@@ -70,9 +215,42 @@ impl std::fmt::Debug for KdfMacType {
 #[non_exhaustive]
 pub enum KdfType {
     KeyBased,
+    // HKDF as specified in RFC 5869
+    Hkdf,
+    // PBKDF2 as specified in RFC 8018
+    Pbkdf2,
+    // scrypt as specified in RFC 7914
+    Scrypt,
+    // Argon2 as specified in RFC 9106, available in OpenSSL 3.2 and later
+    Argon2i,
+    Argon2d,
+    Argon2id,
+    // SSH key derivation as specified in RFC 4253 section 7.2
+    SshKdf,
+    // One-step key derivation as specified in SP800-56C rev2 section 4
+    SingleStep,
+    // ANSI X9.63 key derivation, as validated in SP800-135
+    X963,
+    // ANSI X9.42 key derivation as used in RFC 2631, with a DER-encoded OtherInfo
+    X942Asn1,
+    // ANSI X9.42 key derivation with a concatenated OtherInfo passed as SharedInfo
+    X942Concat,
+    // The TLS 1.0-1.2 PRF, with MD5-SHA1 as digest for TLS 1.0 and 1.1
+    Tls1Prf,
+    // HKDF-Extract and HKDF-Expand-Label from RFC 8446 section 7.1, for TLS and DTLS 1.3
+    Tls13,
+    // The Kerberos DK function from RFC 3961 section 5.1
+    Krb5,
+    // The PKCS#12 KDF from RFC 7292 appendix B, the password is converted to a BMPString
+    Pkcs12,
+    // The AES-CM PRF from RFC 3711 section 4.3, with a 48-bit SRTP or 32-bit SRTCP index
+    Srtp,
+    Srtcp,
+    // An SP800-90A HMAC_DRBG instantiated and generated once, as used for RFC 6979 nonces
+    HmacDrbg,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 #[non_exhaustive]
 pub enum KdfArgument<'a> {
     Key(&'a [u8]),
@@ -84,22 +262,242 @@ pub enum KdfArgument<'a> {
     KbSeed(&'a [u8]),
 
     R(u8),
+    // The counter is required in counter mode, and optional in the other modes
+    UseCounter(bool),
+    CounterLocation(KdfCounterLocation),
     UseSeparator(bool),
     UseL(bool),
     LBits(u8),
+    // SP800-108 uses big-endian, bit-sized L after the context
+    CounterEndianness(KdfEndianness),
+    LEndianness(KdfEndianness),
+    LUnits(KdfLUnits),
+    LPosition(KdfLPosition),
 
     Mac(KdfMacType),
     KbMode(KdfKbMode),
+
+    Digest(openssl::hash::MessageDigest),
+    // Called "info" in RFC 5869
+    Info(&'a [u8]),
+    HkdfMode(KdfHkdfMode),
+
+    Password(&'a [u8]),
+    Iterations(u64),
+    // Disables the SP800-132 lower-bound checks for PBKDF2, as the OpenSSL "pkcs5" parameter
+    Pkcs5(bool),
+
+    ScryptN(u64),
+    ScryptR(u32),
+    ScryptP(u32),
+    MaxMemBytes(u64),
+
+    // Argon2 memory cost in kibibytes
+    MemoryCost(u32),
+    Lanes(u32),
+    Threads(u32),
+    Secret(&'a [u8]),
+    AssociatedData(&'a [u8]),
+
+    // Called "H" in RFC 4253, the shared secret "K" is passed as Key
+    SshXcghash(&'a [u8]),
+    SshSessionId(&'a [u8]),
+    SshType(KdfSshType),
+
+    // Called "FixedInfo" in SP800-56C, the shared secret "Z" is passed as Key
+    FixedInfo(&'a [u8]),
+    // Output size of the KMAC auxiliary function in bytes
+    MacLen(usize),
+
+    // Called "SharedInfo" in ANSI X9.63 and "OtherInfo" in the X9.42 concatenation KDF
+    SharedInfo(&'a [u8]),
+
+    // The optional fields of the X9.42 OtherInfo structure
+    PartyUInfo(&'a [u8]),
+    PartyVInfo(&'a [u8]),
+    SuppPubInfo(&'a [u8]),
+    SuppPrivInfo(&'a [u8]),
+    // Puts the CEK length in bits into suppPubInfo, enabled by default
+    UseKeyBits(bool),
+    CekAlg(KdfCekAlg<'a>),
+
+    // May be passed multiple times, the chunks are concatenated. The TLS secret is passed as Secret
+    TlsSeed(&'a [u8]),
+
+    // "tls13 " for TLS 1.3 and "dtls13" for DTLS 1.3
+    Tls13Prefix(&'a [u8]),
+    Tls13Label(&'a [u8]),
+    // The context of HKDF-Expand-Label, usually a transcript hash
+    Tls13Data(&'a [u8]),
+
+    Cipher(openssl::symm::Cipher),
+    // The Kerberos usage constant, n-folded to the cipher block size if needed
+    Krb5Constant(&'a [u8]),
+
+    Pkcs12Id(KdfPkcs12Id),
+
+    // The master key and master salt are passed as Key and Salt, the cipher as Cipher
+    // The key derivation rate, zero or a power of two up to 2^24
+    SrtpKdr(u32),
+    // The packet index in big-endian byte order, 6 bytes for SRTP and 4 bytes for SRTCP
+    SrtpIndex(&'a [u8]),
+    SrtpLabel(KdfSrtpLabel),
+
+    // The HMAC_DRBG instantiation inputs, the digest is passed as Digest
+    Entropy(&'a [u8]),
+    Nonce(&'a [u8]),
 }
 
-pub fn perform_kdf<'a>(
+impl std::fmt::Debug for KdfArgument<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KdfArgument::Key(key) => f.debug_tuple("Key").field(key).finish(),
+            KdfArgument::Salt(salt) => f.debug_tuple("Salt").field(salt).finish(),
+            KdfArgument::KbInfo(kb_info) => f.debug_tuple("KbInfo").field(kb_info).finish(),
+            KdfArgument::KbSeed(kb_seed) => f.debug_tuple("KbSeed").field(kb_seed).finish(),
+            KdfArgument::R(r) => f.debug_tuple("R").field(r).finish(),
+            KdfArgument::UseCounter(use_counter) => {
+                f.debug_tuple("UseCounter").field(use_counter).finish()
+            }
+            KdfArgument::CounterLocation(location) => {
+                f.debug_tuple("CounterLocation").field(location).finish()
+            }
+            KdfArgument::UseSeparator(use_separator) => {
+                f.debug_tuple("UseSeparator").field(use_separator).finish()
+            }
+            KdfArgument::UseL(use_l) => f.debug_tuple("UseL").field(use_l).finish(),
+            KdfArgument::LBits(lbits) => f.debug_tuple("LBits").field(lbits).finish(),
+            KdfArgument::CounterEndianness(endianness) => f
+                .debug_tuple("CounterEndianness")
+                .field(endianness)
+                .finish(),
+            KdfArgument::LEndianness(endianness) => {
+                f.debug_tuple("LEndianness").field(endianness).finish()
+            }
+            KdfArgument::LUnits(units) => f.debug_tuple("LUnits").field(units).finish(),
+            KdfArgument::LPosition(position) => f.debug_tuple("LPosition").field(position).finish(),
+            KdfArgument::Mac(mac) => f.debug_tuple("Mac").field(mac).finish(),
+            KdfArgument::KbMode(mode) => f.debug_tuple("KbMode").field(mode).finish(),
+            KdfArgument::Digest(md) => write!(f, "Digest({:?})", md.type_().long_name()),
+            KdfArgument::Info(info) => f.debug_tuple("Info").field(info).finish(),
+            KdfArgument::HkdfMode(mode) => f.debug_tuple("HkdfMode").field(mode).finish(),
+            KdfArgument::Password(_) => write!(f, "Password(..)"),
+            KdfArgument::Iterations(iter) => f.debug_tuple("Iterations").field(iter).finish(),
+            KdfArgument::Pkcs5(pkcs5) => f.debug_tuple("Pkcs5").field(pkcs5).finish(),
+            KdfArgument::ScryptN(n) => f.debug_tuple("ScryptN").field(n).finish(),
+            KdfArgument::ScryptR(r) => f.debug_tuple("ScryptR").field(r).finish(),
+            KdfArgument::ScryptP(p) => f.debug_tuple("ScryptP").field(p).finish(),
+            KdfArgument::MaxMemBytes(maxmem) => f.debug_tuple("MaxMemBytes").field(maxmem).finish(),
+            KdfArgument::MemoryCost(memcost) => f.debug_tuple("MemoryCost").field(memcost).finish(),
+            KdfArgument::Lanes(lanes) => f.debug_tuple("Lanes").field(lanes).finish(),
+            KdfArgument::Threads(threads) => f.debug_tuple("Threads").field(threads).finish(),
+            KdfArgument::Secret(_) => write!(f, "Secret(..)"),
+            KdfArgument::AssociatedData(ad) => f.debug_tuple("AssociatedData").field(ad).finish(),
+            KdfArgument::SshXcghash(xcghash) => f.debug_tuple("SshXcghash").field(xcghash).finish(),
+            KdfArgument::SshSessionId(session_id) => {
+                f.debug_tuple("SshSessionId").field(session_id).finish()
+            }
+            KdfArgument::SshType(type_) => f.debug_tuple("SshType").field(type_).finish(),
+            KdfArgument::FixedInfo(fixed_info) => {
+                f.debug_tuple("FixedInfo").field(fixed_info).finish()
+            }
+            KdfArgument::MacLen(maclen) => f.debug_tuple("MacLen").field(maclen).finish(),
+            KdfArgument::SharedInfo(shared_info) => {
+                f.debug_tuple("SharedInfo").field(shared_info).finish()
+            }
+            KdfArgument::PartyUInfo(info) => f.debug_tuple("PartyUInfo").field(info).finish(),
+            KdfArgument::PartyVInfo(info) => f.debug_tuple("PartyVInfo").field(info).finish(),
+            KdfArgument::SuppPubInfo(info) => f.debug_tuple("SuppPubInfo").field(info).finish(),
+            KdfArgument::SuppPrivInfo(_) => write!(f, "SuppPrivInfo(..)"),
+            KdfArgument::UseKeyBits(use_keybits) => {
+                f.debug_tuple("UseKeyBits").field(use_keybits).finish()
+            }
+            KdfArgument::CekAlg(cek_alg) => f.debug_tuple("CekAlg").field(cek_alg).finish(),
+            KdfArgument::TlsSeed(seed) => f.debug_tuple("TlsSeed").field(seed).finish(),
+            KdfArgument::Tls13Prefix(prefix) => f.debug_tuple("Tls13Prefix").field(prefix).finish(),
+            KdfArgument::Tls13Label(label) => f.debug_tuple("Tls13Label").field(label).finish(),
+            KdfArgument::Tls13Data(data) => f.debug_tuple("Tls13Data").field(data).finish(),
+            KdfArgument::Cipher(cipher) => write!(f, "Cipher({:?})", cipher.nid().long_name()),
+            KdfArgument::Krb5Constant(constant) => {
+                f.debug_tuple("Krb5Constant").field(constant).finish()
+            }
+            KdfArgument::Pkcs12Id(id) => f.debug_tuple("Pkcs12Id").field(id).finish(),
+            KdfArgument::SrtpKdr(kdr) => f.debug_tuple("SrtpKdr").field(kdr).finish(),
+            KdfArgument::SrtpIndex(index) => f.debug_tuple("SrtpIndex").field(index).finish(),
+            KdfArgument::SrtpLabel(label) => f.debug_tuple("SrtpLabel").field(label).finish(),
+            KdfArgument::Entropy(_) => write!(f, "Entropy(..)"),
+            KdfArgument::Nonce(nonce) => f.debug_tuple("Nonce").field(nonce).finish(),
+        }
+    }
+}
+
+pub fn perform_kdf(
     type_: KdfType,
-    args: &[&'a KdfArgument],
+    args: &[&KdfArgument],
     length: usize,
+) -> Result<Vec<u8>, KdfError> {
+    checks::check_arguments(type_, args, (length as u64).saturating_mul(8))?;
+
+    try_implementations(|implementation| (implementation.func)(type_, args, length))
+}
+
+// The output is the leftmost length_bits bits, the unused bits of the last byte are zeroed.
+// Only the custom KBKDF supports lengths that are not a multiple of 8, encoding them in L.
+pub fn perform_kdf_bits(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    length_bits: usize,
+) -> Result<Vec<u8>, KdfError> {
+    // The backends disagree on empty output, OpenSSL rejects it
+    if length_bits == 0 {
+        return Err(KdfError::InvalidOption("Output length must not be zero"));
+    }
+    if length_bits % 8 == 0 {
+        return perform_kdf(type_, args, length_bits / 8);
+    }
+
+    checks::check_arguments(type_, args, length_bits as u64)?;
+
+    try_implementations(|implementation| match implementation.func_bits {
+        Some(func_bits) => func_bits(type_, args, length_bits),
+        None => Err(KdfError::UnsupportedOption(format!(
+            "Output length of {} bits",
+            length_bits
+        ))),
+    })
+}
+
+// Derives the sum of the lengths at once and splits it, so that all the keys share one L
+pub fn perform_kdf_split(
+    type_: KdfType,
+    args: &[&KdfArgument],
+    lengths: &[usize],
+) -> Result<Vec<Vec<u8>>, KdfError> {
+    let total = lengths
+        .iter()
+        .try_fold(0usize, |total, length| total.checked_add(*length))
+        .ok_or(KdfError::InvalidOption("Total output length overflows"))?;
+    if total == 0 {
+        return Err(KdfError::InvalidOption("Output length must not be zero"));
+    }
+
+    let mut output = perform_kdf(type_, args, total)?;
+
+    let mut keys = Vec::with_capacity(lengths.len());
+    for length in lengths {
+        let rest = output.split_off(*length);
+        keys.push(output);
+        output = rest;
+    }
+    Ok(keys)
+}
+
+fn try_implementations(
+    perform: impl Fn(&Implementation) -> Result<Vec<u8>, KdfError>,
 ) -> Result<Vec<u8>, KdfError> {
     let mut last_result = None;
     for implementation in AVAILABLE_IMPLEMENTATIONS {
-        last_result = Some((implementation.func)(type_, args, length));
+        last_result = Some(perform(implementation));
         match last_result {
             Some(Err(KdfError::Unimplemented(_))) => continue,
             Some(Err(KdfError::UnsupportedOption(_))) => continue,
@@ -115,7 +513,7 @@ pub fn perform_kdf<'a>(
     }
 }
 
-pub fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
+pub fn supports_args(args: &[&KdfArgument]) -> bool {
     for implementation in AVAILABLE_IMPLEMENTATIONS {
         if (implementation.supports_args)(args) {
             return true;
@@ -125,11 +523,25 @@ pub fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
     false
 }
 
+type ImplementationFunc = dyn Fn(KdfType, &[&KdfArgument], usize) -> Result<Vec<u8>, KdfError>;
+
 struct Implementation {
     supports_args: &'static dyn Fn(&[&KdfArgument]) -> bool,
-    func: &'static dyn Fn(KdfType, &[&KdfArgument], usize) -> Result<Vec<u8>, KdfError>,
+    func: &'static ImplementationFunc,
+    // As func, but with the output length in bits
+    func_bits: Option<&'static ImplementationFunc>,
 }
 
+mod checks;
+mod hmac_drbg;
+mod kbkdf;
+mod pkcs12;
+mod rfc6979;
+mod sshkdf;
+pub use kbkdf::{Kbkdf, KbkdfBuilder};
+pub use rfc6979::rfc6979_nonce;
+pub use sshkdf::{perform_sshkdf, SshKdfKeys};
+
 #[cfg(implementation = "custom")]
 mod custom;
 #[cfg(implementation = "ossl11")]
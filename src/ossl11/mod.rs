@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 mod sys;
 #[macro_use]
 mod utils;
@@ -8,9 +10,10 @@ use crate::{KdfArgument, KdfError, KdfType};
 pub(crate) const IMPLEMENTATION: crate::Implementation = crate::Implementation {
     supports_args: &supports_args,
     func: &perform,
+    func_bits: None,
 };
 
-fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
+fn supports_args(args: &[&KdfArgument]) -> bool {
     use crate::KdfArgument::*;
     for arg in args {
         match arg {
@@ -19,23 +22,78 @@ fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
             KbInfo(_) => {}
             KbSeed(_) => {}
             R(_) => return false,
+            UseCounter(_) => return false,
+            CounterLocation(_) => return false,
             UseSeparator(_) => return false,
             UseL(_) => return false,
             LBits(_) => return false,
-            Mac(_) => {}
-            KbMode(_) => {}
+            CounterEndianness(_) => return false,
+            LEndianness(_) => return false,
+            LUnits(_) => return false,
+            LPosition(_) => return false,
+            Mac(mac) => match mac {
+                crate::KdfMacType::Hmac(_) => {}
+                crate::KdfMacType::Cmac(_) => {}
+                crate::KdfMacType::Kmac128 | crate::KdfMacType::Kmac256 => return false,
+            },
+            KbMode(mode) => match mode {
+                crate::KdfKbMode::Counter | crate::KdfKbMode::Feedback => {}
+                crate::KdfKbMode::DoublePipeline => return false,
+            },
+            Digest(_) => {}
+            Info(_) => {}
+            HkdfMode(_) => {}
+            Password(_) => {}
+            Iterations(_) => {}
+            Pkcs5(_) => {}
+            ScryptN(_) => {}
+            ScryptR(_) => {}
+            ScryptP(_) => {}
+            MaxMemBytes(_) => {}
+            MemoryCost(_) => return false,
+            Lanes(_) => return false,
+            Threads(_) => return false,
+            Secret(_) => {}
+            AssociatedData(_) => return false,
+            SshXcghash(_) => {}
+            SshSessionId(_) => {}
+            SshType(_) => {}
+            FixedInfo(_) => {}
+            MacLen(_) => return false,
+            SharedInfo(_) => return false,
+            PartyUInfo(_) => return false,
+            PartyVInfo(_) => return false,
+            SuppPubInfo(_) => return false,
+            SuppPrivInfo(_) => return false,
+            UseKeyBits(_) => return false,
+            CekAlg(_) => return false,
+            TlsSeed(_) => {}
+            Tls13Prefix(_) => return false,
+            Tls13Label(_) => return false,
+            Tls13Data(_) => return false,
+            Cipher(_) => {}
+            Krb5Constant(_) => {}
+            Pkcs12Id(_) => return false,
+            SrtpKdr(_) => return false,
+            SrtpIndex(_) => return false,
+            SrtpLabel(_) => return false,
+            Entropy(_) => return false,
+            Nonce(_) => return false,
         }
     }
 
     true
 }
 
-fn perform<'a>(
+fn perform(
     type_: crate::KdfType,
-    args: &[&'a KdfArgument],
+    args: &[&KdfArgument],
     length: usize,
 ) -> core::result::Result<Vec<u8>, KdfError> {
-    let mut kdf = Kdf::new(type_)?;
+    let type_id = type_
+        .type_id()
+        .ok_or(KdfError::Unimplemented("KDF not available in OpenSSL 1.1"))?;
+    let mut kdf = Kdf::new(type_id)?;
 
     for arg in args {
         match arg {
@@ -52,10 +110,14 @@ fn perform<'a>(
                 kdf.set_kb_seed(kb_seed)?;
             }
             KdfArgument::KbMode(mode) => {
-                kdf.set_kb_mode((*mode).into())?;
+                kdf.set_kb_mode(KdfKbMode::try_from(*mode)?)?;
+            }
+            KdfArgument::Mac(_) if matches!(type_, KdfType::SingleStep) => {
+                // The SSKDF backport only supports the hash auxiliary function
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
             }
             KdfArgument::Mac(mac) => {
-                kdf.set_kb_mac_type((*mac).into())?;
+                kdf.set_kb_mac_type(KdfMacType::try_from(*mac)?)?;
                 if mac.has_md() {
                     kdf.set_digest(*mac.get_md().unwrap())?;
                 }
@@ -66,6 +128,12 @@ fn perform<'a>(
             KdfArgument::R(_) => {
                 return Err(KdfError::UnsupportedOption("R".to_string()));
             }
+            KdfArgument::UseCounter(_) => {
+                return Err(KdfError::UnsupportedOption("UseCounter".to_string()));
+            }
+            KdfArgument::CounterLocation(_) => {
+                return Err(KdfError::UnsupportedOption("CounterLocation".to_string()));
+            }
             KdfArgument::UseSeparator(_) => {
                 return Err(KdfError::UnsupportedOption("UseSeparator".to_string()));
             }
@@ -75,6 +143,102 @@ fn perform<'a>(
             KdfArgument::LBits(_) => {
                 return Err(KdfError::UnsupportedOption("LBits".to_string()));
             }
+            KdfArgument::CounterEndianness(_)
+            | KdfArgument::LEndianness(_)
+            | KdfArgument::LUnits(_)
+            | KdfArgument::LPosition(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::Digest(md) => {
+                kdf.set_digest(*md)?;
+            }
+            KdfArgument::Info(info) => {
+                kdf.add_hkdf_info(info)?;
+            }
+            KdfArgument::HkdfMode(mode) => {
+                kdf.set_hkdf_mode((*mode).into())?;
+            }
+            KdfArgument::Password(password) => {
+                kdf.set_pass(password)?;
+            }
+            KdfArgument::Iterations(iter) => {
+                let iter = i32::try_from(*iter)
+                    .map_err(|_| KdfError::InvalidOption("Iterations too large"))?;
+                kdf.set_iter(iter)?;
+            }
+            KdfArgument::Pkcs5(_) => {
+                // There is no ctrl for this, the SP800-132 checks are done in crate::checks
+            }
+            KdfArgument::ScryptN(n) => {
+                kdf.set_scrypt_n(*n)?;
+            }
+            KdfArgument::ScryptR(r) => {
+                kdf.set_scrypt_r(*r)?;
+            }
+            KdfArgument::ScryptP(p) => {
+                kdf.set_scrypt_p(*p)?;
+            }
+            KdfArgument::MaxMemBytes(maxmem) => {
+                kdf.set_maxmem_bytes(*maxmem)?;
+            }
+            KdfArgument::MemoryCost(_)
+            | KdfArgument::Lanes(_)
+            | KdfArgument::Threads(_)
+            | KdfArgument::AssociatedData(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::Secret(secret) => {
+                kdf.set_tls_secret(secret)?;
+            }
+            KdfArgument::TlsSeed(seed) => {
+                kdf.add_tls_seed(seed)?;
+            }
+            KdfArgument::Tls13Prefix(_)
+            | KdfArgument::Tls13Label(_)
+            | KdfArgument::Tls13Data(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::SshXcghash(xcghash) => {
+                kdf.set_sshkdf_xcghash(xcghash)?;
+            }
+            KdfArgument::SshSessionId(session_id) => {
+                kdf.set_sshkdf_session_id(session_id)?;
+            }
+            KdfArgument::SshType(type_) => {
+                kdf.set_sshkdf_type(type_.letter() as i32)?;
+            }
+            KdfArgument::FixedInfo(fixed_info) => {
+                kdf.set_sskdf_info(fixed_info)?;
+            }
+            KdfArgument::MacLen(_) => {
+                return Err(KdfError::UnsupportedOption("MacLen".to_string()));
+            }
+            KdfArgument::SharedInfo(_) => {
+                return Err(KdfError::UnsupportedOption("SharedInfo".to_string()));
+            }
+            KdfArgument::PartyUInfo(_)
+            | KdfArgument::PartyVInfo(_)
+            | KdfArgument::SuppPubInfo(_)
+            | KdfArgument::SuppPrivInfo(_)
+            | KdfArgument::UseKeyBits(_)
+            | KdfArgument::CekAlg(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::Cipher(cipher) => {
+                kdf.set_cipher(*cipher)?;
+            }
+            KdfArgument::Krb5Constant(constant) => {
+                kdf.set_krb5kdf_constant(constant)?;
+            }
+            KdfArgument::Pkcs12Id(_) => {
+                return Err(KdfError::UnsupportedOption("Pkcs12Id".to_string()));
+            }
+            KdfArgument::SrtpKdr(_) | KdfArgument::SrtpIndex(_) | KdfArgument::SrtpLabel(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::Entropy(_) | KdfArgument::Nonce(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
         }
     }
 
@@ -132,23 +296,67 @@ enum KdfKbMode {
     Feedback = 1,
 }
 
-impl From<crate::KdfKbMode> for KdfKbMode {
-    fn from(mode: crate::KdfKbMode) -> Self {
+impl TryFrom<crate::KdfKbMode> for KdfKbMode {
+    type Error = KdfError;
+
+    fn try_from(mode: crate::KdfKbMode) -> core::result::Result<Self, Self::Error> {
         match mode {
-            crate::KdfKbMode::Counter => KdfKbMode::Counter,
-            crate::KdfKbMode::Feedback => KdfKbMode::Feedback,
+            crate::KdfKbMode::Counter => Ok(KdfKbMode::Counter),
+            crate::KdfKbMode::Feedback => Ok(KdfKbMode::Feedback),
+            crate::KdfKbMode::DoublePipeline => {
+                Err(KdfError::UnsupportedOption(format!("{:?}", mode)))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum KdfHkdfMode {
+    ExtractAndExpand = 0,
+    ExtractOnly = 1,
+    ExpandOnly = 2,
+}
+
+impl From<crate::KdfHkdfMode> for KdfHkdfMode {
+    fn from(mode: crate::KdfHkdfMode) -> Self {
+        match mode {
+            crate::KdfHkdfMode::ExtractAndExpand => KdfHkdfMode::ExtractAndExpand,
+            crate::KdfHkdfMode::ExtractOnly => KdfHkdfMode::ExtractOnly,
+            crate::KdfHkdfMode::ExpandOnly => KdfHkdfMode::ExpandOnly,
         }
     }
 }
 
 impl KdfType {
-    fn type_id(&self) -> i32 {
+    fn type_id(&self) -> Option<i32> {
         match self {
-            KdfType::KeyBased => 1204,
+            KdfType::KeyBased => Some(1204),
+            KdfType::Hkdf => Some(1036),
+            KdfType::Pbkdf2 => Some(69),
+            KdfType::Scrypt => Some(973),
+            KdfType::Argon2i | KdfType::Argon2d | KdfType::Argon2id => None,
+            KdfType::SshKdf => Some(1203),
+            KdfType::SingleStep => Some(1205),
+            KdfType::X963 | KdfType::X942Asn1 | KdfType::X942Concat => None,
+            KdfType::Tls1Prf => Some(1021),
+            KdfType::Tls13 => None,
+            // The NID of KRB5KDF is specific to the backport, so it is looked up by name
+            KdfType::Krb5 => nid_from_short_name(b"KRB5KDF\0"),
+            KdfType::Pkcs12 => None,
+            KdfType::Srtp | KdfType::Srtcp => None,
+            KdfType::HmacDrbg => None,
         }
     }
 }
 
+fn nid_from_short_name(short_name: &[u8]) -> Option<i32> {
+    match unsafe { openssl_sys::OBJ_sn2nid(short_name.as_ptr() as *const libc::c_char) } {
+        0 => None,
+        nid => Some(nid),
+    }
+}
+
 #[derive(Debug)]
 #[repr(i32)]
 enum KdfMacType {
@@ -156,19 +364,24 @@ enum KdfMacType {
     Cmac = 1,
 }
 
-impl From<crate::KdfMacType> for KdfMacType {
-    fn from(value: crate::KdfMacType) -> Self {
+impl TryFrom<crate::KdfMacType> for KdfMacType {
+    type Error = KdfError;
+
+    fn try_from(value: crate::KdfMacType) -> core::result::Result<Self, Self::Error> {
         match value {
-            crate::KdfMacType::Hmac(_) => KdfMacType::Hmac,
-            crate::KdfMacType::Cmac(_) => KdfMacType::Cmac,
+            crate::KdfMacType::Hmac(_) => Ok(KdfMacType::Hmac),
+            crate::KdfMacType::Cmac(_) => Ok(KdfMacType::Cmac),
+            crate::KdfMacType::Kmac128 | crate::KdfMacType::Kmac256 => {
+                Err(KdfError::UnsupportedOption(format!("{:?}", value)))
+            }
         }
     }
 }
 
 impl Kdf {
-    fn new(type_: KdfType) -> Result<Self> {
+    fn new(type_id: i32) -> Result<Self> {
         unsafe {
-            let kdf = Kdf::from_ptr(cvt_p(sys::EVP_KDF_CTX_new_id(type_.type_id()))?);
+            let kdf = Kdf::from_ptr(cvt_p(sys::EVP_KDF_CTX_new_id(type_id))?);
             Ok(kdf)
         }
     }
@@ -198,6 +411,164 @@ impl Kdf {
         }
     }
 
+    fn set_hkdf_mode(&mut self, mode: KdfHkdfMode) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetHkdfMode as i32,
+                mode as i32,
+            ))
+        }
+    }
+
+    fn add_hkdf_info(&mut self, info: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::AddHkdfInfo as i32,
+                info.as_ptr(),
+                info.len(),
+            ))
+        }
+    }
+
+    fn set_pass(&mut self, pass: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetPass as i32,
+                pass.as_ptr(),
+                pass.len(),
+            ))
+        }
+    }
+
+    fn set_iter(&mut self, iter: i32) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetIter as i32,
+                iter,
+            ))
+        }
+    }
+
+    fn set_scrypt_n(&mut self, n: u64) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetScryptN as i32,
+                n,
+            ))
+        }
+    }
+
+    fn set_scrypt_r(&mut self, r: u32) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetScryptR as i32,
+                r,
+            ))
+        }
+    }
+
+    fn set_scrypt_p(&mut self, p: u32) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetScryptP as i32,
+                p,
+            ))
+        }
+    }
+
+    fn set_maxmem_bytes(&mut self, maxmem: u64) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetMaxmemBytes as i32,
+                maxmem,
+            ))
+        }
+    }
+
+    fn set_sshkdf_xcghash(&mut self, xcghash: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetSshkdfXcghash as i32,
+                xcghash.as_ptr(),
+                xcghash.len(),
+            ))
+        }
+    }
+
+    fn set_sshkdf_session_id(&mut self, session_id: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetSshkdfSessionId as i32,
+                session_id.as_ptr(),
+                session_id.len(),
+            ))
+        }
+    }
+
+    fn set_sshkdf_type(&mut self, type_: i32) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetSshkdfType as i32,
+                type_,
+            ))
+        }
+    }
+
+    fn set_sskdf_info(&mut self, info: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetSskdfInfo as i32,
+                info.as_ptr(),
+                info.len(),
+            ))
+        }
+    }
+
+    fn set_tls_secret(&mut self, secret: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetTlsSecret as i32,
+                secret.as_ptr(),
+                secret.len(),
+            ))
+        }
+    }
+
+    fn add_tls_seed(&mut self, seed: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::AddTlsSeed as i32,
+                seed.as_ptr(),
+                seed.len(),
+            ))
+        }
+    }
+
+    fn set_krb5kdf_constant(&mut self, constant: &[u8]) -> Result<i32> {
+        unsafe {
+            cvt(sys::EVP_KDF_ctrl(
+                self.as_ptr(),
+                KdfControlOption::SetKrb5kdfConstant as i32,
+                constant.as_ptr(),
+                constant.len(),
+            ))
+        }
+    }
+
     fn set_salt(&mut self, salt: &[u8]) -> Result<i32> {
         unsafe {
             cvt(sys::EVP_KDF_ctrl(
@@ -1,6 +1,7 @@
 use std::ffi::{CStr, CString};
 
 use libc::c_char;
+use openssl::{hash::MessageDigest, symm::Cipher};
 
 use crate::{KdfArgument, KdfError};
 
@@ -12,30 +13,77 @@ use params::ParamsBuilder;
 use utils::{cvt, cvt_cp, cvt_p};
 
 impl crate::KdfKbMode {
-    fn to_param(&self) -> &'static [u8] {
+    fn to_param(self) -> Result<&'static [u8], KdfError> {
         use crate::KdfKbMode::*;
         match self {
-            Counter => b"counter\0",
-            Feedback => b"feedback\0",
+            Counter => Ok(b"counter\0"),
+            Feedback => Ok(b"feedback\0"),
+            DoublePipeline => Err(KdfError::UnsupportedOption(format!("{:?}", self))),
+        }
+    }
+}
+
+impl crate::KdfHkdfMode {
+    fn to_param(self) -> &'static [u8] {
+        use crate::KdfHkdfMode::*;
+        match self {
+            ExtractAndExpand => b"EXTRACT_AND_EXPAND\0",
+            ExtractOnly => b"EXTRACT_ONLY\0",
+            ExpandOnly => b"EXPAND_ONLY\0",
+        }
+    }
+}
+
+impl crate::KdfSshType {
+    // SSHKDF requires a data size of exactly one, so these are not NUL-terminated
+    fn to_param(self) -> &'static [u8] {
+        use crate::KdfSshType::*;
+        match self {
+            InitialIvClientToServer => b"A",
+            InitialIvServerToClient => b"B",
+            EncryptionKeyClientToServer => b"C",
+            EncryptionKeyServerToClient => b"D",
+            IntegrityKeyClientToServer => b"E",
+            IntegrityKeyServerToClient => b"F",
         }
     }
 }
 
 impl crate::KdfMacType {
-    fn to_param(&self) -> &'static [u8] {
+    fn to_param(self) -> &'static [u8] {
         use crate::KdfMacType::*;
         match self {
             Hmac(_) => b"HMAC\0",
             Cmac(_) => b"CMAC\0",
+            Kmac128 => b"KMAC128\0",
+            Kmac256 => b"KMAC256\0",
         }
     }
 }
 
 impl crate::KdfType {
-    fn to_name(&self) -> CString {
+    fn to_name(self) -> CString {
         use crate::KdfType::*;
         match self {
             KeyBased => CString::new("KBKDF").unwrap(),
+            Hkdf => CString::new("HKDF").unwrap(),
+            Pbkdf2 => CString::new("PBKDF2").unwrap(),
+            Scrypt => CString::new("SCRYPT").unwrap(),
+            Argon2i => CString::new("ARGON2I").unwrap(),
+            Argon2d => CString::new("ARGON2D").unwrap(),
+            Argon2id => CString::new("ARGON2ID").unwrap(),
+            SshKdf => CString::new("SSHKDF").unwrap(),
+            SingleStep => CString::new("SSKDF").unwrap(),
+            X963 => CString::new("X963KDF").unwrap(),
+            X942Asn1 => CString::new("X942KDF-ASN1").unwrap(),
+            X942Concat => CString::new("X942KDF-CONCAT").unwrap(),
+            Tls1Prf => CString::new("TLS1-PRF").unwrap(),
+            Tls13 => CString::new("TLS13-KDF").unwrap(),
+            Krb5 => CString::new("KRB5KDF").unwrap(),
+            Pkcs12 => CString::new("PKCS12KDF").unwrap(),
+            Srtp => CString::new("SRTPKDF").unwrap(),
+            Srtcp => CString::new("SRTCPKDF").unwrap(),
+            HmacDrbg => CString::new("HMAC-DRBG-KDF").unwrap(),
         }
     }
 }
@@ -43,9 +91,10 @@ impl crate::KdfType {
 pub(crate) const IMPLEMENTATION: crate::Implementation = crate::Implementation {
     supports_args: &supports_args,
     func: &perform,
+    func_bits: None,
 };
 
-fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
+fn supports_args(args: &[&KdfArgument]) -> bool {
     use crate::KdfArgument::*;
     for arg in args {
         match arg {
@@ -57,20 +106,83 @@ fn supports_args<'a>(args: &[&'a KdfArgument]) -> bool {
                 #[cfg(not(ossl3_supported = "kbkdf_r"))]
                 return false;
             }
+            UseCounter(use_counter) => {
+                if !use_counter {
+                    return false;
+                }
+            }
+            CounterLocation(location) => match location {
+                crate::KdfCounterLocation::BeforeFixed => {}
+                _ => return false,
+            },
             UseSeparator(_) => {}
             UseL(_) => {}
             LBits(_) => return false,
+            CounterEndianness(endianness) | LEndianness(endianness) => match endianness {
+                crate::KdfEndianness::Big => {}
+                _ => return false,
+            },
+            LUnits(units) => match units {
+                crate::KdfLUnits::Bits => {}
+                _ => return false,
+            },
+            LPosition(position) => match position {
+                crate::KdfLPosition::AfterContext => {}
+                _ => return false,
+            },
             Mac(_) => {}
-            KbMode(_) => {}
+            KbMode(mode) => match mode {
+                crate::KdfKbMode::Counter | crate::KdfKbMode::Feedback => {}
+                crate::KdfKbMode::DoublePipeline => return false,
+            },
+            Digest(_) => {}
+            Info(_) => {}
+            HkdfMode(_) => {}
+            Password(_) => {}
+            Iterations(_) => {}
+            Pkcs5(_) => {}
+            ScryptN(_) => {}
+            ScryptR(_) => {}
+            ScryptP(_) => {}
+            MaxMemBytes(_) => {}
+            MemoryCost(_) => {}
+            Lanes(_) => {}
+            Threads(_) => {}
+            Secret(_) => {}
+            AssociatedData(_) => {}
+            SshXcghash(_) => {}
+            SshSessionId(_) => {}
+            SshType(_) => {}
+            FixedInfo(_) => {}
+            MacLen(_) => {}
+            SharedInfo(_) => {}
+            PartyUInfo(_) => {}
+            PartyVInfo(_) => {}
+            SuppPubInfo(_) => {}
+            SuppPrivInfo(_) => {}
+            UseKeyBits(_) => {}
+            CekAlg(_) => {}
+            TlsSeed(_) => {}
+            Tls13Prefix(_) => {}
+            Tls13Label(_) => {}
+            Tls13Data(_) => {}
+            Cipher(_) => {}
+            Krb5Constant(_) => {}
+            Pkcs12Id(_) => {}
+            SrtpKdr(_) => {}
+            SrtpIndex(_) => {}
+            SrtpLabel(_) => {}
+            Entropy(_) => {}
+            Nonce(_) => {}
         }
     }
 
     true
 }
 
-fn perform<'a>(
+fn perform(
     type_: crate::KdfType,
-    args: &[&'a KdfArgument],
+    args: &[&KdfArgument],
     length: usize,
 ) -> Result<Vec<u8>, KdfError> {
     let mut builder = ParamsBuilder::with_capacity(args.len());
@@ -84,6 +196,16 @@ fn perform<'a>(
             KdfArgument::R(r) => {
                 builder.add_i32(sys::OSSL_KDF_PARAM_KBKDF_R, *r as i32)?;
             }
+            // OpenSSL always includes the counter
+            KdfArgument::UseCounter(use_counter) => {
+                if !use_counter {
+                    return Err(KdfError::UnsupportedOption("UseCounter".to_string()));
+                }
+            }
+            KdfArgument::CounterLocation(location) => match location {
+                crate::KdfCounterLocation::BeforeFixed => {}
+                _ => return Err(KdfError::UnsupportedOption(format!("{:?}", arg))),
+            },
             KdfArgument::UseSeparator(use_separator) => builder.add_i32(
                 sys::OSSL_KDF_PARAM_KBKDF_USE_SEPARATOR,
                 if *use_separator { 1 } else { 0 },
@@ -92,31 +214,163 @@ fn perform<'a>(
                 builder.add_i32(sys::OSSL_KDF_PARAM_KBKDF_USE_L, if *use_l { 1 } else { 0 })?
             }
             KdfArgument::Mac(mac_type) => {
+                #[cfg(not(ossl3_supported = "kbkdf_kmac"))]
+                if matches!(type_, crate::KdfType::KeyBased)
+                    && matches!(
+                        mac_type,
+                        crate::KdfMacType::Kmac128 | crate::KdfMacType::Kmac256
+                    )
+                {
+                    return Err(KdfError::UnsupportedOption("KBKDF with KMAC".to_string()));
+                }
                 builder.add_string(sys::OSSL_KDF_PARAM_MAC, mac_type.to_param())?;
                 match mac_type {
-                    crate::KdfMacType::Hmac(md) => {
-                        let md_name = unsafe { cvt_cp(sys::EVP_MD_get0_name(md.as_ptr())) }?;
-                        let md_name = unsafe { CStr::from_ptr(md_name) };
-                        builder
-                            .add_string(sys::OSSL_KDF_PARAM_DIGEST, md_name.to_bytes_with_nul())?;
-                    }
-                    crate::KdfMacType::Cmac(cipher) => {
-                        let cipher_name =
-                            unsafe { cvt_cp(sys::EVP_CIPHER_get0_name(cipher.as_ptr())) }?;
-                        let cipher_name = unsafe { CStr::from_ptr(cipher_name) };
-                        builder.add_string(
-                            sys::OSSL_KDF_PARAM_CIPHER,
-                            cipher_name.to_bytes_with_nul(),
-                        )?;
-                    }
+                    crate::KdfMacType::Hmac(md) => add_digest(&mut builder, md)?,
+                    crate::KdfMacType::Cmac(cipher) => add_cipher(&mut builder, cipher)?,
+                    crate::KdfMacType::Kmac128 | crate::KdfMacType::Kmac256 => {}
                 }
             }
             KdfArgument::KbMode(kb_mode) => {
-                builder.add_string(sys::OSSL_KDF_PARAM_MODE, kb_mode.to_param())?
+                builder.add_string(sys::OSSL_KDF_PARAM_MODE, kb_mode.to_param()?)?
             }
             KdfArgument::LBits(_) => {
                 return Err(KdfError::UnsupportedOption("LBits".to_string()));
             }
+            // OpenSSL only implements the SP800-108 encoding
+            KdfArgument::CounterEndianness(crate::KdfEndianness::Big)
+            | KdfArgument::LEndianness(crate::KdfEndianness::Big)
+            | KdfArgument::LUnits(crate::KdfLUnits::Bits)
+            | KdfArgument::LPosition(crate::KdfLPosition::AfterContext) => {}
+            KdfArgument::CounterEndianness(_)
+            | KdfArgument::LEndianness(_)
+            | KdfArgument::LUnits(_)
+            | KdfArgument::LPosition(_) => {
+                return Err(KdfError::UnsupportedOption(format!("{:?}", arg)));
+            }
+            KdfArgument::Digest(md) => add_digest(&mut builder, md)?,
+            KdfArgument::Info(info) => builder.add_slice(sys::OSSL_KDF_PARAM_INFO, info)?,
+            KdfArgument::HkdfMode(mode) => {
+                builder.add_string(sys::OSSL_KDF_PARAM_MODE, mode.to_param())?
+            }
+            KdfArgument::Password(password) => match type_ {
+                crate::KdfType::Pkcs12 => builder.add_slice(
+                    sys::OSSL_KDF_PARAM_PASSWORD,
+                    &crate::pkcs12::to_bmp_string(password)?,
+                )?,
+                _ => builder.add_slice(sys::OSSL_KDF_PARAM_PASSWORD, password)?,
+            },
+            KdfArgument::Iterations(iter) => builder.add_u64(sys::OSSL_KDF_PARAM_ITER, *iter)?,
+            KdfArgument::Pkcs5(pkcs5) => {
+                builder.add_i32(sys::OSSL_KDF_PARAM_PKCS5, if *pkcs5 { 1 } else { 0 })?
+            }
+            KdfArgument::ScryptN(n) => builder.add_u64(sys::OSSL_KDF_PARAM_SCRYPT_N, *n)?,
+            KdfArgument::ScryptR(r) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_SCRYPT_R, u64::from(*r))?
+            }
+            KdfArgument::ScryptP(p) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_SCRYPT_P, u64::from(*p))?
+            }
+            KdfArgument::MaxMemBytes(maxmem) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_SCRYPT_MAXMEM, *maxmem)?
+            }
+            KdfArgument::MemoryCost(memcost) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_ARGON2_MEMCOST, u64::from(*memcost))?
+            }
+            KdfArgument::Lanes(lanes) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_ARGON2_LANES, u64::from(*lanes))?
+            }
+            KdfArgument::Threads(threads) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_THREADS, u64::from(*threads))?
+            }
+            KdfArgument::Secret(secret) => builder.add_slice(sys::OSSL_KDF_PARAM_SECRET, secret)?,
+            KdfArgument::AssociatedData(ad) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_ARGON2_AD, ad)?
+            }
+            KdfArgument::SshXcghash(xcghash) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_SSHKDF_XCGHASH, xcghash)?
+            }
+            KdfArgument::SshSessionId(session_id) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_SSHKDF_SESSION_ID, session_id)?
+            }
+            KdfArgument::SshType(type_) => {
+                builder.add_string(sys::OSSL_KDF_PARAM_SSHKDF_TYPE, type_.to_param())?
+            }
+            KdfArgument::FixedInfo(fixed_info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_INFO, fixed_info)?
+            }
+            KdfArgument::MacLen(maclen) => {
+                builder.add_u64(sys::OSSL_KDF_PARAM_MAC_SIZE, *maclen as u64)?
+            }
+            KdfArgument::SharedInfo(shared_info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_INFO, shared_info)?
+            }
+            KdfArgument::PartyUInfo(info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_X942_PARTYUINFO, info)?
+            }
+            KdfArgument::PartyVInfo(info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_X942_PARTYVINFO, info)?
+            }
+            KdfArgument::SuppPubInfo(info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_X942_SUPP_PUBINFO, info)?
+            }
+            KdfArgument::SuppPrivInfo(info) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_X942_SUPP_PRIVINFO, info)?
+            }
+            KdfArgument::UseKeyBits(use_keybits) => builder.add_i32(
+                sys::OSSL_KDF_PARAM_X942_USE_KEYBITS,
+                if *use_keybits { 1 } else { 0 },
+            )?,
+            KdfArgument::CekAlg(cek_alg) => match cek_alg {
+                crate::KdfCekAlg::Cipher(cipher) => {
+                    let cipher_name =
+                        unsafe { cvt_cp(sys::EVP_CIPHER_get0_name(cipher.as_ptr())) }?;
+                    let cipher_name = unsafe { CStr::from_ptr(cipher_name) };
+                    builder
+                        .add_string(sys::OSSL_KDF_PARAM_CEK_ALG, cipher_name.to_bytes_with_nul())?
+                }
+                crate::KdfCekAlg::Name(name) => {
+                    let name = CString::new(*name)
+                        .map_err(|_| KdfError::InvalidOption("Invalid CEK algorithm name"))?;
+                    builder.add_string(sys::OSSL_KDF_PARAM_CEK_ALG, name.as_bytes_with_nul())?
+                }
+            },
+            KdfArgument::TlsSeed(seed) => builder.add_slice(sys::OSSL_KDF_PARAM_SEED, seed)?,
+            KdfArgument::Tls13Prefix(prefix) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_PREFIX, prefix)?
+            }
+            KdfArgument::Tls13Label(label) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_LABEL, label)?
+            }
+            KdfArgument::Tls13Data(data) => builder.add_slice(sys::OSSL_KDF_PARAM_DATA, data)?,
+            KdfArgument::Cipher(cipher) => add_cipher(&mut builder, cipher)?,
+            KdfArgument::Krb5Constant(constant) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_CONSTANT, constant)?
+            }
+            KdfArgument::Pkcs12Id(id) => {
+                builder.add_i32(sys::OSSL_KDF_PARAM_PKCS12_ID, i32::from(id.id()))?
+            }
+            // OpenSSL takes the rate as log2(kdr) + 1, leaving zero to mean no rate
+            KdfArgument::SrtpKdr(kdr) => builder.add_u64(
+                sys::OSSL_KDF_PARAM_SRTPKDF_KDR,
+                if *kdr == 0 {
+                    0
+                } else {
+                    u64::from(kdr.trailing_zeros()) + 1
+                },
+            )?,
+            KdfArgument::SrtpIndex(index) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_SRTPKDF_INDEX, index)?
+            }
+            KdfArgument::SrtpLabel(label) => builder.add_u64(
+                sys::OSSL_KDF_PARAM_SRTPKDF_LABEL,
+                u64::from(label.label(type_)),
+            )?,
+            KdfArgument::Entropy(entropy) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_HMACDRBG_ENTROPY, entropy)?
+            }
+            KdfArgument::Nonce(nonce) => {
+                builder.add_slice(sys::OSSL_KDF_PARAM_HMACDRBG_NONCE, nonce)?
+            }
         }
     }
     let mut params = builder.build();
@@ -130,7 +384,9 @@ fn perform<'a>(
             std::ptr::null(),
         );
         if ptr.is_null() {
-            Err(KdfError::UnsupportedOption("No such KDF".to_string()))
+            // Clear the error queue, older libcrypto versions simply do not have this KDF
+            drop(openssl::error::ErrorStack::get());
+            Err(KdfError::Unimplemented("KDF not available in libcrypto"))
         } else {
             Ok(ptr)
         }
@@ -153,6 +409,18 @@ fn perform<'a>(
     Ok(output)
 }
 
+fn add_cipher(builder: &mut ParamsBuilder, cipher: &Cipher) -> Result<(), KdfError> {
+    let cipher_name = unsafe { cvt_cp(sys::EVP_CIPHER_get0_name(cipher.as_ptr())) }?;
+    let cipher_name = unsafe { CStr::from_ptr(cipher_name) };
+    builder.add_string(sys::OSSL_KDF_PARAM_CIPHER, cipher_name.to_bytes_with_nul())
+}
+
+fn add_digest(builder: &mut ParamsBuilder, md: &MessageDigest) -> Result<(), KdfError> {
+    let md_name = unsafe { cvt_cp(sys::EVP_MD_get0_name(md.as_ptr())) }?;
+    let md_name = unsafe { CStr::from_ptr(md_name) };
+    builder.add_string(sys::OSSL_KDF_PARAM_DIGEST, md_name.to_bytes_with_nul())
+}
+
 const DUBIOUS_PARAMS: &[&str] = &["r"];
 
 struct KDFContext {
@@ -189,8 +457,7 @@ impl KDFContext {
                     MAX_NUMBER_OF_PARAMS,
                 )
             };
-            for i in 0..MAX_NUMBER_OF_PARAMS {
-                let supported_param = &all_params[i];
+            for supported_param in all_params {
                 if supported_param.data_type == 0 {
                     break;
                 }
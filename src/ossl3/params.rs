@@ -12,6 +12,7 @@ use crate::KdfError;
 
 enum Param {
     I32(*mut c_int),
+    U64(*mut u64),
     String(*mut c_char, usize),
     Vec(*mut c_void, usize),
 }
@@ -30,10 +31,24 @@ impl Param {
         Ok(Param::I32(p))
     }
 
+    fn alloc_u64(val: u64) -> Result<Param, KdfError> {
+        let p = unsafe {
+            cvt_p(openssl_sys::CRYPTO_malloc(
+                mem::size_of::<u64>(),
+                concat!(file!(), "\0").as_ptr() as *const _,
+                line!() as c_int,
+            ))
+        }? as *mut u64;
+        unsafe { *p = val };
+
+        Ok(Param::U64(p))
+    }
+
     fn alloc_string(val: &[u8]) -> Result<Param, KdfError> {
         let p = unsafe {
+            // CRYPTO_malloc returns NULL for zero-sized allocations
             cvt_p(openssl_sys::CRYPTO_malloc(
-                val.len(),
+                val.len().max(1),
                 concat!(file!(), "\0").as_ptr() as *const _,
                 line!() as c_int,
             ))
@@ -45,8 +60,9 @@ impl Param {
 
     fn alloc_vec(val: &[u8]) -> Result<Param, KdfError> {
         let p = unsafe {
+            // CRYPTO_malloc returns NULL for zero-sized allocations
             cvt_p(openssl_sys::CRYPTO_malloc(
-                val.len(),
+                val.len().max(1),
                 concat!(file!(), "\0").as_ptr() as *const _,
                 line!() as c_int,
             ))
@@ -72,6 +88,7 @@ impl Drop for Param {
         unsafe {
             match *self {
                 Param::I32(p) => drop_param!(p),
+                Param::U64(p) => drop_param!(p),
                 Param::String(p, _) => drop_param!(p),
                 Param::Vec(p, _) => drop_param!(p),
             }
@@ -109,6 +126,10 @@ impl ParamsBuilder {
                         let pname = *name as *const c_char;
                         super::sys::OSSL_PARAM_construct_int(pname, *v)
                     }
+                    U64(v) => {
+                        let pname = *name as *const c_char;
+                        super::sys::OSSL_PARAM_construct_uint64(pname, *v)
+                    }
                     Vec(buf, len) => {
                         let pname = *name as *const c_char;
                         super::sys::OSSL_PARAM_construct_octet_string(pname, *buf, *len)
@@ -138,6 +159,7 @@ macro_rules! add_construct {
 }
 
 add_construct!(add_i32, alloc_i32, i32);
+add_construct!(add_u64, alloc_u64, u64);
 add_construct!(add_string, alloc_string, &[u8]);
 add_construct!(add_slice, alloc_vec, &[u8]);
 
@@ -78,6 +78,18 @@ pub const OSSL_KDF_PARAM_X942_PARTYVINFO: *const u8 = b"partyv-info\0" as *const
 pub const OSSL_KDF_PARAM_X942_SUPP_PUBINFO: *const u8 = b"supp-pubinfo\0" as *const u8;
 pub const OSSL_KDF_PARAM_X942_SUPP_PRIVINFO: *const u8 = b"supp-privinfo\0" as *const u8;
 pub const OSSL_KDF_PARAM_X942_USE_KEYBITS: *const u8 = b"use-keybits\0" as *const u8;
+pub const OSSL_KDF_PARAM_PREFIX: *const u8 = b"prefix\0" as *const u8;
+pub const OSSL_KDF_PARAM_LABEL: *const u8 = b"label\0" as *const u8;
+pub const OSSL_KDF_PARAM_DATA: *const u8 = b"data\0" as *const u8;
+pub const OSSL_KDF_PARAM_SRTPKDF_KDR: *const u8 = b"kdr\0" as *const u8;
+pub const OSSL_KDF_PARAM_SRTPKDF_INDEX: *const u8 = b"index\0" as *const u8;
+pub const OSSL_KDF_PARAM_SRTPKDF_LABEL: *const u8 = b"label\0" as *const u8;
+pub const OSSL_KDF_PARAM_HMACDRBG_ENTROPY: *const u8 = b"entropy\0" as *const u8;
+pub const OSSL_KDF_PARAM_HMACDRBG_NONCE: *const u8 = b"nonce\0" as *const u8;
+pub const OSSL_KDF_PARAM_THREADS: *const u8 = b"threads\0" as *const u8;
+pub const OSSL_KDF_PARAM_ARGON2_AD: *const u8 = b"ad\0" as *const u8;
+pub const OSSL_KDF_PARAM_ARGON2_LANES: *const u8 = b"lanes\0" as *const u8;
+pub const OSSL_KDF_PARAM_ARGON2_MEMCOST: *const u8 = b"memcost\0" as *const u8;
 
 /* Known KDF names */
 pub const OSSL_KDF_NAME_HKDF: *const u8 = b"HKDF\0" as *const u8;
@@ -86,8 +98,16 @@ pub const OSSL_KDF_NAME_SCRYPT: *const u8 = b"SCRYPT\0" as *const u8;
 pub const OSSL_KDF_NAME_SSHKDF: *const u8 = b"SSHKDF\0" as *const u8;
 pub const OSSL_KDF_NAME_SSKDF: *const u8 = b"SSKDF\0" as *const u8;
 pub const OSSL_KDF_NAME_TLS1_PRF: *const u8 = b"TLS1-PRF\0" as *const u8;
+pub const OSSL_KDF_NAME_TLS1_3_KDF: *const u8 = b"TLS13-KDF\0" as *const u8;
 pub const OSSL_KDF_NAME_X942KDF_ASN1: *const u8 = b"X942KDF-ASN1\0" as *const u8;
 pub const OSSL_KDF_NAME_X942KDF_CONCAT: *const u8 = b"X942KDF-CONCAT\0" as *const u8;
 pub const OSSL_KDF_NAME_X963KDF: *const u8 = b"X963KDF\0" as *const u8;
 pub const OSSL_KDF_NAME_KBKDF: *const u8 = b"KBKDF\0" as *const u8;
 pub const OSSL_KDF_NAME_KRB5KDF: *const u8 = b"KRB5KDF\0" as *const u8;
+pub const OSSL_KDF_NAME_PKCS12KDF: *const u8 = b"PKCS12KDF\0" as *const u8;
+pub const OSSL_KDF_NAME_SRTPKDF: *const u8 = b"SRTPKDF\0" as *const u8;
+pub const OSSL_KDF_NAME_SRTCPKDF: *const u8 = b"SRTCPKDF\0" as *const u8;
+pub const OSSL_KDF_NAME_HMACDRBGKDF: *const u8 = b"HMAC-DRBG-KDF\0" as *const u8;
+pub const OSSL_KDF_NAME_ARGON2I: *const u8 = b"ARGON2I\0" as *const u8;
+pub const OSSL_KDF_NAME_ARGON2D: *const u8 = b"ARGON2D\0" as *const u8;
+pub const OSSL_KDF_NAME_ARGON2ID: *const u8 = b"ARGON2ID\0" as *const u8;
@@ -0,0 +1,14 @@
+use crate::KdfError;
+
+// RFC 7292 appendix B.1: the password is a NUL-terminated BMPString, i.e. big-endian UTF-16
+pub(crate) fn to_bmp_string(password: &[u8]) -> Result<Vec<u8>, KdfError> {
+    let password = std::str::from_utf8(password)
+        .map_err(|_| KdfError::InvalidOption("PKCS#12 password is not valid UTF-8"))?;
+
+    let mut output = Vec::with_capacity((password.len() + 1) * 2);
+    for unit in password.encode_utf16() {
+        output.extend_from_slice(&unit.to_be_bytes());
+    }
+    output.extend_from_slice(&[0, 0]);
+    Ok(output)
+}
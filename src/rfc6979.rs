@@ -0,0 +1,62 @@
+use openssl::{
+    bn::{BigNum, BigNumRef},
+    hash::MessageDigest,
+};
+
+use crate::{hmac_drbg::HmacDrbg, KdfError};
+
+// bits2int from RFC 6979 section 2.3.2, keeping the leftmost qlen bits
+fn bits2int(input: &[u8], qlen: usize) -> Result<BigNum, KdfError> {
+    let mut value = BigNum::from_slice(input)?;
+    if input.len() * 8 > qlen {
+        let mut shifted = BigNum::new()?;
+        shifted.rshift(&value, (input.len() * 8 - qlen) as i32)?;
+        value = shifted;
+    }
+    Ok(value)
+}
+
+/// Generates the deterministic DSA or ECDSA nonce k as specified in RFC 6979 section 3.2.
+///
+/// `order` is the (sub)group order q, `private_key` the private key x and `hash` the
+/// message hash H(m). `md` is the digest used for the HMAC_DRBG, usually the one that
+/// produced `hash`.
+pub fn rfc6979_nonce(
+    md: MessageDigest,
+    order: &BigNumRef,
+    private_key: &BigNumRef,
+    hash: &[u8],
+) -> Result<BigNum, KdfError> {
+    let qlen = order.num_bits() as usize;
+    let rlen = qlen.div_ceil(8);
+
+    if qlen < 2 || private_key.is_negative() || private_key.num_bits() == 0 {
+        return Err(KdfError::InvalidOption(
+            "Invalid group order or private key",
+        ));
+    }
+    if private_key >= order {
+        return Err(KdfError::InvalidOption(
+            "Private key must be smaller than the group order",
+        ));
+    }
+
+    // bits2octets(h1): bits2int reduced modulo q, which needs at most one subtraction
+    let mut h1 = bits2int(hash, qlen)?;
+    if h1.as_ref() >= order {
+        let mut reduced = BigNum::new()?;
+        reduced.checked_sub(&h1, order)?;
+        h1 = reduced;
+    }
+
+    let entropy = private_key.to_vec_padded(rlen as i32)?;
+    let nonce = h1.to_vec_padded(rlen as i32)?;
+    let mut drbg = HmacDrbg::new(md, &entropy, &nonce)?;
+
+    loop {
+        let k = bits2int(&drbg.generate(rlen)?, qlen)?;
+        if k.num_bits() != 0 && k.as_ref() < order {
+            return Ok(k);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use openssl::{hash::MessageDigest, symm::Cipher};
+
+use crate::{perform_kdf, KdfArgument, KdfError, KdfSshType, KdfType};
+
+/// The six values derived from the shared secret as described in RFC 4253 section 7.2
+#[derive(Clone)]
+pub struct SshKdfKeys {
+    pub iv_client_to_server: Vec<u8>,
+    pub iv_server_to_client: Vec<u8>,
+    pub encryption_key_client_to_server: Vec<u8>,
+    pub encryption_key_server_to_client: Vec<u8>,
+    pub integrity_key_client_to_server: Vec<u8>,
+    pub integrity_key_server_to_client: Vec<u8>,
+}
+
+/// Derives all IVs, encryption keys and integrity keys for an SSH connection.
+///
+/// `key` is the shared secret K, encoded as an mpint, `xcghash` the exchange hash H.
+/// The IV and encryption key lengths are taken from `cipher`.
+pub fn perform_sshkdf(
+    md: MessageDigest,
+    key: &[u8],
+    xcghash: &[u8],
+    session_id: &[u8],
+    cipher: Cipher,
+    mac_key_len: usize,
+) -> Result<SshKdfKeys, KdfError> {
+    let iv_len = cipher.iv_len().unwrap_or(0);
+    let key_len = cipher.key_len();
+
+    let derive = |type_: KdfSshType, length: usize| -> Result<Vec<u8>, KdfError> {
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        let args = [
+            &KdfArgument::Digest(md),
+            &KdfArgument::Key(key),
+            &KdfArgument::SshXcghash(xcghash),
+            &KdfArgument::SshSessionId(session_id),
+            &KdfArgument::SshType(type_),
+        ];
+        perform_kdf(KdfType::SshKdf, &args, length)
+    };
+
+    Ok(SshKdfKeys {
+        iv_client_to_server: derive(KdfSshType::InitialIvClientToServer, iv_len)?,
+        iv_server_to_client: derive(KdfSshType::InitialIvServerToClient, iv_len)?,
+        encryption_key_client_to_server: derive(KdfSshType::EncryptionKeyClientToServer, key_len)?,
+        encryption_key_server_to_client: derive(KdfSshType::EncryptionKeyServerToClient, key_len)?,
+        integrity_key_client_to_server: derive(
+            KdfSshType::IntegrityKeyClientToServer,
+            mac_key_len,
+        )?,
+        integrity_key_server_to_client: derive(
+            KdfSshType::IntegrityKeyServerToClient,
+            mac_key_len,
+        )?,
+    })
+}
@@ -8,11 +8,15 @@ mod tests {
     const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
     #[allow(unused_imports)]
-    use crate::{KdfArgument, KdfError, KdfKbMode, KdfMacType, KdfType};
+    use crate::{
+        KdfArgument, KdfCekAlg, KdfCounterLocation, KdfEndianness, KdfError, KdfHkdfMode,
+        KdfKbMode, KdfLPosition, KdfLUnits, KdfMacType, KdfPkcs12Id, KdfSrtpLabel, KdfSshType,
+        KdfType,
+    };
     #[allow(unused_imports)]
-    use openssl::{hash::MessageDigest, nid::Nid, symm::Cipher};
+    use openssl::{bn::BigNum, hash::MessageDigest, nid::Nid, symm::Cipher};
 
-    fn cavp_tests_that_should_pass() -> u64 {
+    fn cavp_tests_that_should_pass(mode: KdfKbMode) -> u64 {
         let mut num_that_should_pass = 0;
 
         #[cfg(implementation = "ossl11")]
@@ -22,22 +26,39 @@ mod tests {
         }
         #[cfg(implementation = "ossl3")]
         {
+            // OpenSSL only puts the counter right after the iteration variable
             #[cfg(not(ossl3_supported = "kbkdf_r"))]
-            const CAVP_SHOULD_PASS_OSSL3: u64 = 320;
+            let cavp_should_pass_ossl3: u64 = match mode {
+                KdfKbMode::Counter => 400,
+                KdfKbMode::Feedback => 254,
+                KdfKbMode::DoublePipeline => 0,
+            };
             #[cfg(ossl3_supported = "kbkdf_r")]
-            const CAVP_SHOULD_PASS_OSSL3: u64 = 1280;
-            num_that_should_pass = std::cmp::max(num_that_should_pass, CAVP_SHOULD_PASS_OSSL3);
+            let cavp_should_pass_ossl3: u64 = match mode {
+                KdfKbMode::Counter => 1600,
+                KdfKbMode::Feedback => 299,
+                KdfKbMode::DoublePipeline => 0,
+            };
+            num_that_should_pass = std::cmp::max(num_that_should_pass, cavp_should_pass_ossl3);
         }
         #[cfg(implementation = "custom")]
         {
-            const CAVP_SHOULD_PASS_CUSTOM: u64 = 1280;
-            num_that_should_pass = std::cmp::max(num_that_should_pass, CAVP_SHOULD_PASS_CUSTOM);
+            let cavp_should_pass_custom: u64 = match mode {
+                KdfKbMode::Counter => 4800,
+                KdfKbMode::Feedback => 299,
+                KdfKbMode::DoublePipeline => 299,
+            };
+            num_that_should_pass = std::cmp::max(num_that_should_pass, cavp_should_pass_custom);
         }
         #[cfg(all(implementation = "ossl3", implementation = "custom"))]
         {
-            const CAVP_SHOULD_PASS_CUSTOM_AND_OSSL3: u64 = 960;
+            let cavp_should_pass_custom_and_ossl3: u64 = match mode {
+                KdfKbMode::Counter => 960,
+                KdfKbMode::Feedback => 299,
+                KdfKbMode::DoublePipeline => 299,
+            };
             num_that_should_pass =
-                std::cmp::max(num_that_should_pass, CAVP_SHOULD_PASS_CUSTOM_AND_OSSL3);
+                std::cmp::max(num_that_should_pass, cavp_should_pass_custom_and_ossl3);
         }
 
         num_that_should_pass
@@ -58,6 +79,8 @@ mod tests {
             "AES128" => Cipher::aes_128_cbc(),
             "AES192" => Cipher::aes_192_cbc(),
             "AES256" => Cipher::aes_256_cbc(),
+            "TDES2" => Cipher::from_nid(Nid::DES_EDE_CBC).unwrap(),
+            "TDES3" => Cipher::des_ede3_cbc(),
             _ => panic!("Unsupported CMAC cipher: {}", value),
         }
     }
@@ -75,7 +98,25 @@ mod tests {
 
     #[test]
     fn cavp_kbkdf_counter_mode() {
-        let input_file = std::path::PathBuf::from(&MANIFEST_DIR).join("test_assets/KDFCTR_gen.rsp");
+        cavp_kbkdf("test_assets/KDFCTR_gen.rsp", KdfKbMode::Counter);
+    }
+
+    #[test]
+    fn cavp_kbkdf_feedback_mode() {
+        cavp_kbkdf("test_assets/KDFFeedback_botan.rsp", KdfKbMode::Feedback);
+    }
+
+    #[test]
+    fn cavp_kbkdf_double_pipeline_mode() {
+        cavp_kbkdf(
+            "test_assets/KDFDblPipeline_botan.rsp",
+            KdfKbMode::DoublePipeline,
+        );
+    }
+
+    // Runs a CAVP KBKDF response file, in the NIST layout for the given mode
+    fn cavp_kbkdf(path: &str, mode: KdfKbMode) {
+        let input_file = std::path::PathBuf::from(&MANIFEST_DIR).join(path);
         let input_file = std::fs::File::open(input_file).unwrap();
         let reader = std::io::BufReader::new(input_file).lines();
 
@@ -85,53 +126,46 @@ mod tests {
         let mut num_failed = 0;
 
         let mut mac: Option<KdfMacType> = None;
-        let mut skip_prf = false;
-        let mut correct_ctrlocation = false;
+        let mut ctrlocation: Option<String> = None;
         let mut rlen: Option<u8> = None;
+        let mut iv: Option<Vec<u8>> = None;
         let mut count: Option<u64> = None;
         let mut len: Option<usize> = None;
         let mut ki: Option<Vec<u8>> = None;
         let mut fixed_input: Option<Vec<u8>> = None;
+        let mut data_before_ctr: Option<Vec<u8>> = None;
 
         for line in reader {
             let line = line.unwrap();
             let line = line.trim();
-            if line.len() == 0 || line.starts_with("#") {
-                continue;
-            }
-            let (key, value) = parse_kv(&line);
-            if key != "PRF" && skip_prf {
-                continue;
-            }
-            if (key != "PRF" && key != "CTRLOCATION") && !correct_ctrlocation {
+            if line.is_empty() || line.starts_with('#') {
                 continue;
             }
+            let (key, value) = parse_kv(line);
             let expected = match key {
                 "PRF" => {
-                    skip_prf = false;
                     let (prf_type, prf_name) = value.split_once('_').unwrap();
                     match prf_type {
-                        "CMAC" => match prf_name {
-                            "TDES2" | "TDES3" => skip_prf = true,
-                            name => mac = Some(KdfMacType::Cmac(cmac_to_cipher(name))),
-                        },
+                        "CMAC" => mac = Some(KdfMacType::Cmac(cmac_to_cipher(prf_name))),
                         "HMAC" => mac = Some(KdfMacType::Hmac(hmac_to_md(prf_name))),
                         _ => panic!("unknown PRF type: {}", prf_type),
                     }
                     continue;
                 }
                 "CTRLOCATION" => {
-                    correct_ctrlocation = value == "BEFORE_FIXED";
+                    ctrlocation = Some(value.to_string());
                     continue;
                 }
                 "RLEN" => {
-                    rlen = Some(match value {
-                        "8_BITS" => 8,
-                        "16_BITS" => 16,
-                        "24_BITS" => 24,
-                        "32_BITS" => 32,
+                    // Without a counter, the width is NONE
+                    rlen = match value {
+                        "8_BITS" => Some(8),
+                        "16_BITS" => Some(16),
+                        "24_BITS" => Some(24),
+                        "32_BITS" => Some(32),
+                        "NONE" => None,
                         _ => panic!("unsupported rlen: {}", value),
-                    });
+                    };
                     continue;
                 }
                 "COUNT" => {
@@ -146,13 +180,28 @@ mod tests {
                     ki = Some(hex::decode(value).unwrap());
                     continue;
                 }
-                "FixedInputDataByteLen" => {
+                "FixedInputDataByteLen" | "DataBeforeCtrLen" | "DataAfterCtrLen" | "IVlen" => {
+                    continue;
+                }
+                "IV" => {
+                    iv = Some(hex::decode(value).unwrap());
                     continue;
                 }
                 "FixedInputData" => {
                     fixed_input = Some(hex::decode(value).unwrap());
                     continue;
                 }
+                "DataBeforeCtrData" => {
+                    data_before_ctr = Some(hex::decode(value).unwrap());
+                    continue;
+                }
+                "DataAfterCtrData" => {
+                    // The counter goes between the two parts of the fixed input data
+                    let mut data = data_before_ctr.clone().unwrap();
+                    data.extend_from_slice(&hex::decode(value).unwrap());
+                    fixed_input = Some(data);
+                    continue;
+                }
                 "KO" => {
                     hex::decode(value).unwrap()
                     // Not continuing, we have the info to execute this test case
@@ -163,27 +212,50 @@ mod tests {
             let mac = mac.unwrap();
             let fixed_input = fixed_input.as_ref().unwrap();
             let ki = ki.as_ref().unwrap();
-            let rlen = rlen.unwrap();
             let len = len.unwrap();
+            // The iteration variable comes first, so AFTER_ITER is before the fixed input data
+            let location = match ctrlocation.as_deref().unwrap() {
+                "BEFORE_FIXED" | "AFTER_ITER" => Some(KdfCounterLocation::BeforeFixed),
+                "AFTER_FIXED" => Some(KdfCounterLocation::AfterFixed),
+                "MIDDLE_FIXED" => Some(KdfCounterLocation::MiddleFixed(
+                    data_before_ctr.as_ref().unwrap().len(),
+                )),
+                "BEFORE_ITER" => Some(KdfCounterLocation::BeforeIter),
+                "NONE" => None,
+                value => panic!("unsupported ctrlocation: {}", value),
+            };
             let print_descrip = || {
-                eprintln!("\tExecuting CAVP case, prf: {:?}, ctrlocation: {:?}, rlen: {:?}, count: {:?}, len: {:?}, ki: {:?}, fixed_input: {:?}, expected: {:?}", mac, correct_ctrlocation, rlen, count, len, ki, fixed_input, expected)
+                eprintln!("\tExecuting CAVP case, prf: {:?}, ctrlocation: {:?}, rlen: {:?}, count: {:?}, len: {:?}, ki: {:?}, fixed_input: {:?}, expected: {:?}", mac, location, rlen, count, len, ki, fixed_input, expected)
             };
 
+            let mode_arg = KdfArgument::KbMode(mode);
             let mac_arg = KdfArgument::Mac(mac);
             let fixed_input_arg = KdfArgument::Salt(fixed_input);
             let ki_arg = KdfArgument::Key(ki);
-            let rlen_arg = KdfArgument::R(rlen);
+            let rlen_arg = rlen.map(KdfArgument::R);
+            let location_arg = location.map(KdfArgument::CounterLocation);
+            let iv_arg = iv.as_deref().map(KdfArgument::KbSeed);
 
             let mut args = vec![
-                &KdfArgument::KbMode(KdfKbMode::Counter),
+                &mode_arg,
                 &mac_arg,
                 &fixed_input_arg,
                 &ki_arg,
                 &KdfArgument::UseSeparator(false),
                 &KdfArgument::UseL(false),
             ];
-            if rlen != 32 {
-                args.push(&rlen_arg);
+            match &rlen_arg {
+                Some(KdfArgument::R(32)) => {}
+                Some(rlen_arg) => args.push(rlen_arg),
+                None => args.push(&KdfArgument::UseCounter(false)),
+            }
+            if let Some(location_arg) = &location_arg {
+                if !matches!(location, Some(KdfCounterLocation::BeforeFixed)) {
+                    args.push(location_arg);
+                }
+            }
+            if let Some(iv_arg) = &iv_arg {
+                args.push(iv_arg);
             }
             if !crate::supports_args(&args) {
                 if CAVP_PRINT_SKIP.is_some() {
@@ -216,7 +288,7 @@ mod tests {
             }
         }
 
-        let should_pass = cavp_tests_that_should_pass();
+        let should_pass = cavp_tests_that_should_pass(mode);
 
         eprintln!("CAVP results:");
         eprintln!("\tExecuted: {}", num_executed);
@@ -258,8 +330,1998 @@ mod tests {
         );
     }
 
+    // The IEEE 802.11 KDF: HMAC over a 16-bit little-endian counter, the label, the context
+    // and the 16-bit little-endian length in bits, computed directly
+    #[cfg(implementation = "custom")]
+    fn ieee80211_kdf_reference(key: &[u8], label: &[u8], context: &[u8], length: usize) -> Vec<u8> {
+        let hmac_key = openssl::pkey::PKey::hmac(key).unwrap();
+        let mut output = Vec::new();
+        let mut i: u16 = 1;
+        while output.len() < length {
+            let mut signer =
+                openssl::sign::Signer::new(MessageDigest::sha256(), &hmac_key).unwrap();
+            signer.update(&i.to_le_bytes()).unwrap();
+            signer.update(label).unwrap();
+            signer.update(context).unwrap();
+            signer.update(&(length as u16 * 8).to_le_bytes()).unwrap();
+            output.extend_from_slice(&signer.sign_to_vec().unwrap());
+            i += 1;
+        }
+        output.truncate(length);
+        output
+    }
+
+    #[test]
+    fn kbkdf_counter_and_l_encoding() {
+        let key = [0x2b; 32];
+        let label = b"Pairwise key expansion";
+        let context = [0x77; 76];
+
+        // IEEE 802.11 PTK derivation, 48 bytes for a CCMP-128 PTK
+        let args = [
+            &KdfArgument::Mac(KdfMacType::Hmac(MessageDigest::sha256())),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(label),
+            &KdfArgument::KbInfo(&context),
+            &KdfArgument::UseSeparator(false),
+            &KdfArgument::R(16),
+            &KdfArgument::LBits(16),
+            &KdfArgument::CounterEndianness(KdfEndianness::Little),
+            &KdfArgument::LEndianness(KdfEndianness::Little),
+        ];
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 48);
+
+        #[cfg(implementation = "custom")]
+        assert_eq!(
+            key_out.unwrap(),
+            ieee80211_kdf_reference(&key, label, &context, 48)
+        );
+        #[cfg(not(implementation = "custom"))]
+        assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+
+        // L as a byte count before the context: PRF(KI, [i] || Label || 0x00 || [L] || Context)
+        let args = [
+            &KdfArgument::Mac(KdfMacType::Hmac(MessageDigest::sha256())),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(label),
+            &KdfArgument::KbInfo(&context),
+            &KdfArgument::LUnits(KdfLUnits::Bytes),
+            &KdfArgument::LPosition(KdfLPosition::BeforeContext),
+        ];
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 20);
+
+        #[cfg(implementation = "custom")]
+        {
+            let mut input = 1u32.to_be_bytes().to_vec();
+            input.extend_from_slice(label);
+            input.push(0x00);
+            input.extend_from_slice(&20u32.to_be_bytes());
+            input.extend_from_slice(&context);
+
+            let hmac_key = openssl::pkey::PKey::hmac(&key).unwrap();
+            let mut signer =
+                openssl::sign::Signer::new(MessageDigest::sha256(), &hmac_key).unwrap();
+            signer.update(&input).unwrap();
+            let expected = signer.sign_to_vec().unwrap();
+
+            assert_eq!(key_out.unwrap(), expected[..20]);
+        }
+        #[cfg(not(implementation = "custom"))]
+        assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+    }
+
+    struct KmacVector {
+        mac: KdfMacType,
+        data: &'static str,
+        custom: &'static [u8],
+        output: &'static str,
+    }
+
+    // The NIST SP800-185 KMAC samples, as KBKDF with KMAC is KMAC(Key, Context, L, Label)
+    const KMAC_KEY: &str = "404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f";
+    const KMAC_LONG_DATA: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\
+         202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f\
+         404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f\
+         606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f\
+         808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f\
+         a0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3b4b5b6b7b8b9babbbcbdbebf\
+         c0c1c2c3c4c5c6c7";
+    const KMAC_VECTORS: &[KmacVector] = &[
+        KmacVector {
+            mac: KdfMacType::Kmac128,
+            data: "00010203",
+            custom: b"",
+            output: "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e",
+        },
+        KmacVector {
+            mac: KdfMacType::Kmac128,
+            data: "00010203",
+            custom: b"My Tagged Application",
+            output: "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5",
+        },
+        KmacVector {
+            mac: KdfMacType::Kmac128,
+            data: KMAC_LONG_DATA,
+            custom: b"My Tagged Application",
+            output: "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230",
+        },
+        KmacVector {
+            mac: KdfMacType::Kmac256,
+            data: "00010203",
+            custom: b"My Tagged Application",
+            output: "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+                     f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+        },
+        KmacVector {
+            mac: KdfMacType::Kmac256,
+            data: KMAC_LONG_DATA,
+            custom: b"",
+            output: "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691\
+                     589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69",
+        },
+        KmacVector {
+            mac: KdfMacType::Kmac256,
+            data: KMAC_LONG_DATA,
+            custom: b"My Tagged Application",
+            output: "b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d9\
+                     70fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965",
+        },
+    ];
+
+    #[test]
+    fn kbkdf_kmac_sp800_185() {
+        let key = hex::decode(KMAC_KEY).unwrap();
+
+        for vector in KMAC_VECTORS {
+            let data = hex::decode(vector.data).unwrap();
+            let expected = hex::decode(vector.output).unwrap();
+
+            let args = [
+                &KdfArgument::Mac(vector.mac),
+                &KdfArgument::Key(&key),
+                &KdfArgument::KbInfo(&data),
+                &KdfArgument::Salt(vector.custom),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, expected.len());
+
+            #[cfg(any(implementation = "custom", ossl3_supported = "kbkdf_kmac"))]
+            assert_eq!(key_out.unwrap(), expected);
+            #[cfg(not(any(implementation = "custom", ossl3_supported = "kbkdf_kmac")))]
+            assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+        }
+    }
+
+    #[test]
+    fn kbkdf_kmac_rejects_encoding_options() {
+        let key = hex::decode(KMAC_KEY).unwrap();
+        let seed = [0xa5; 32];
+
+        for option in [
+            KdfArgument::KbMode(KdfKbMode::Feedback),
+            KdfArgument::KbMode(KdfKbMode::DoublePipeline),
+            KdfArgument::KbSeed(&seed),
+            KdfArgument::R(8),
+            KdfArgument::UseCounter(false),
+            KdfArgument::CounterLocation(KdfCounterLocation::AfterFixed),
+            KdfArgument::CounterEndianness(KdfEndianness::Little),
+            KdfArgument::UseSeparator(false),
+            KdfArgument::UseL(false),
+            KdfArgument::LBits(16),
+            KdfArgument::LEndianness(KdfEndianness::Little),
+            KdfArgument::LUnits(KdfLUnits::Bytes),
+            KdfArgument::LPosition(KdfLPosition::BeforeContext),
+        ] {
+            let args = [
+                &KdfArgument::Mac(KdfMacType::Kmac128),
+                &KdfArgument::Key(&key),
+                &option,
+            ];
+
+            assert!(matches!(
+                crate::perform_kdf(KdfType::KeyBased, &args, 32),
+                Err(KdfError::InvalidOption(_))
+            ));
+            #[cfg(implementation = "custom")]
+            assert!(matches!(
+                (crate::custom::IMPLEMENTATION.func)(KdfType::KeyBased, &args, 32),
+                Err(KdfError::InvalidOption(_))
+            ));
+        }
+    }
+
+    struct HkdfVector {
+        md: &'static str,
+        ikm: &'static str,
+        salt: Option<&'static str>,
+        info: &'static str,
+        prk: &'static str,
+        okm: &'static str,
+    }
+
+    // Test vectors from RFC 5869 Appendix A
+    const HKDF_RFC5869_VECTORS: &[HkdfVector] = &[
+        HkdfVector {
+            md: "SHA256",
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: Some("000102030405060708090a0b0c"),
+            info: "f0f1f2f3f4f5f6f7f8f9",
+            prk: "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+            okm: "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+        },
+        HkdfVector {
+            md: "SHA256",
+            ikm: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\
+                  202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f\
+                  404142434445464748494a4b4c4d4e4f",
+            salt: Some("606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f\
+                        808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f\
+                        a0a1a2a3a4a5a6a7a8a9aaabacadaeaf"),
+            info: "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecf\
+                   d0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeef\
+                   f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+            prk: "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+            okm: "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+                  59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+                  cc30c58179ec3e87c14c01d5c1f3434f1d87",
+        },
+        HkdfVector {
+            md: "SHA256",
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+            salt: Some(""),
+            info: "",
+            prk: "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+            okm: "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+        },
+        HkdfVector {
+            md: "SHA1",
+            ikm: "0b0b0b0b0b0b0b0b0b0b0b",
+            salt: Some("000102030405060708090a0b0c"),
+            info: "f0f1f2f3f4f5f6f7f8f9",
+            prk: "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243",
+            okm: "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896",
+        },
+        HkdfVector {
+            md: "SHA1",
+            ikm: "0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+            salt: None,
+            info: "",
+            prk: "2adccada18779e7c2077ad2eb19d3f3e731385dd",
+            okm: "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48",
+        },
+    ];
+
+    #[test]
+    fn hkdf_rfc5869() {
+        for vector in HKDF_RFC5869_VECTORS {
+            let digest_arg = KdfArgument::Digest(hmac_to_md(vector.md));
+            let ikm = hex::decode(vector.ikm).unwrap();
+            let salt = vector.salt.map(|salt| hex::decode(salt).unwrap());
+            let info = hex::decode(vector.info).unwrap();
+            let prk = hex::decode(vector.prk).unwrap();
+            let okm = hex::decode(vector.okm).unwrap();
+
+            let ikm_arg = KdfArgument::Key(&ikm);
+            let info_arg = KdfArgument::Info(&info);
+            let salt_arg = salt.as_ref().map(|salt| KdfArgument::Salt(salt));
+
+            let mut args = vec![&digest_arg, &ikm_arg, &info_arg];
+            if let Some(salt_arg) = &salt_arg {
+                args.push(salt_arg);
+            }
+            let key_out = crate::perform_kdf(KdfType::Hkdf, &args, okm.len()).unwrap();
+            assert_eq!(key_out, okm);
+
+            let mut args = vec![
+                &KdfArgument::HkdfMode(KdfHkdfMode::ExtractOnly),
+                &digest_arg,
+                &ikm_arg,
+            ];
+            if let Some(salt_arg) = &salt_arg {
+                args.push(salt_arg);
+            }
+            let key_out = crate::perform_kdf(KdfType::Hkdf, &args, prk.len()).unwrap();
+            assert_eq!(key_out, prk);
+
+            let prk_arg = KdfArgument::Key(&prk);
+            let args = [
+                &KdfArgument::HkdfMode(KdfHkdfMode::ExpandOnly),
+                &digest_arg,
+                &prk_arg,
+                &info_arg,
+            ];
+            let key_out = crate::perform_kdf(KdfType::Hkdf, &args, okm.len()).unwrap();
+            assert_eq!(key_out, okm);
+        }
+    }
+
+    // Test vectors from RFC 6070
+    const PBKDF2_RFC6070_VECTORS: &[(&[u8], &[u8], u64, &str)] = &[
+        (
+            b"password",
+            b"salt",
+            1,
+            "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+        ),
+        (
+            b"password",
+            b"salt",
+            2,
+            "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+        ),
+        (
+            b"password",
+            b"salt",
+            4096,
+            "4b007901b765489abead49d926f721d065a429c1",
+        ),
+        (
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+        ),
+        (
+            b"pass\0word",
+            b"sa\0lt",
+            4096,
+            "56fa6aa75548099dcc37d7f03425e0c3",
+        ),
+    ];
+
+    #[test]
+    fn pbkdf2_rfc6070() {
+        for (password, salt, iterations, expected) in PBKDF2_RFC6070_VECTORS {
+            let expected = hex::decode(expected).unwrap();
+
+            // These vectors do not meet the SP800-132 lower bounds
+            let args = [
+                &KdfArgument::Digest(MessageDigest::sha1()),
+                &KdfArgument::Password(password),
+                &KdfArgument::Salt(salt),
+                &KdfArgument::Iterations(*iterations),
+                &KdfArgument::Pkcs5(true),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::Pbkdf2, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    #[test]
+    fn pbkdf2_hmac_sha256() {
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha256()),
+            &KdfArgument::Password(b"passwordPASSWORDpassword"),
+            &KdfArgument::Salt(b"saltSALTsaltSALTsaltSALTsaltSALTsalt"),
+            &KdfArgument::Iterations(4096),
+        ];
+
+        let key_out = crate::perform_kdf(KdfType::Pbkdf2, &args, 40).unwrap();
+
+        assert_eq!(
+            key_out,
+            hex::decode(
+                "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9"
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn pbkdf2_sp800_132_lower_bounds() {
+        let salt = [0x5a; 16];
+        let cases: [(&[u8], u64, usize); 3] =
+            [(&salt, 999, 14), (&salt[..15], 1000, 14), (&salt, 1000, 13)];
+
+        for (salt, iterations, length) in cases {
+            let args = [
+                &KdfArgument::Digest(MessageDigest::sha256()),
+                &KdfArgument::Password(b"password"),
+                &KdfArgument::Salt(salt),
+                &KdfArgument::Iterations(iterations),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::Pbkdf2, &args, length);
+            assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+        }
+
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha256()),
+            &KdfArgument::Password(b"password"),
+            &KdfArgument::Salt(&salt),
+            &KdfArgument::Iterations(1000),
+        ];
+        assert!(crate::perform_kdf(KdfType::Pbkdf2, &args, 14).is_ok());
+    }
+
+    struct ScryptVector {
+        password: &'static [u8],
+        salt: &'static [u8],
+        n: u64,
+        r: u32,
+        p: u32,
+        dk: &'static str,
+    }
+
+    // Test vectors from RFC 7914 section 12
+    const SCRYPT_RFC7914_VECTORS: &[ScryptVector] = &[
+        ScryptVector {
+            password: b"",
+            salt: b"",
+            n: 16,
+            r: 1,
+            p: 1,
+            dk: "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+                 fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+        },
+        ScryptVector {
+            password: b"password",
+            salt: b"NaCl",
+            n: 1024,
+            r: 8,
+            p: 16,
+            dk: "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                 2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+        },
+        ScryptVector {
+            password: b"pleaseletmein",
+            salt: b"SodiumChloride",
+            n: 16384,
+            r: 8,
+            p: 1,
+            dk: "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2\
+                 d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+        },
+    ];
+
+    #[test]
+    fn scrypt_rfc7914() {
+        for vector in SCRYPT_RFC7914_VECTORS {
+            let expected = hex::decode(vector.dk).unwrap();
+
+            let args = [
+                &KdfArgument::Password(vector.password),
+                &KdfArgument::Salt(vector.salt),
+                &KdfArgument::ScryptN(vector.n),
+                &KdfArgument::ScryptR(vector.r),
+                &KdfArgument::ScryptP(vector.p),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::Scrypt, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    #[test]
+    fn scrypt_memory_limit() {
+        // 128 * r * (N + p + 2) bytes are needed, which is 16 MiB + 3 KiB here
+        let args = [
+            &KdfArgument::Password(b"pleaseletmein"),
+            &KdfArgument::Salt(b"SodiumChloride"),
+            &KdfArgument::ScryptN(16384),
+            &KdfArgument::ScryptR(8),
+            &KdfArgument::ScryptP(1),
+            &KdfArgument::MaxMemBytes(16 * 1024 * 1024),
+        ];
+
+        let key_out = crate::perform_kdf(KdfType::Scrypt, &args, 64);
+        assert!(matches!(
+            key_out,
+            Err(KdfError::MemoryLimitExceeded(16780288, 16777216))
+        ));
+    }
+
+    // Test vectors from RFC 9106 section 5
+    const ARGON2_RFC9106_VECTORS: &[(KdfType, &str)] = &[
+        (
+            KdfType::Argon2d,
+            "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb",
+        ),
+        (
+            KdfType::Argon2i,
+            "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+        ),
+        (
+            KdfType::Argon2id,
+            "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+        ),
+    ];
+
+    #[test]
+    fn argon2_rfc9106() {
+        let password = [0x01; 32];
+        let salt = [0x02; 16];
+        let secret = [0x03; 8];
+        let ad = [0x04; 12];
+
+        for (type_, expected) in ARGON2_RFC9106_VECTORS {
+            let expected = hex::decode(expected).unwrap();
+
+            let args = [
+                &KdfArgument::Password(&password),
+                &KdfArgument::Salt(&salt),
+                &KdfArgument::Secret(&secret),
+                &KdfArgument::AssociatedData(&ad),
+                &KdfArgument::MemoryCost(32),
+                &KdfArgument::Iterations(3),
+                &KdfArgument::Lanes(4),
+            ];
+
+            let key_out = crate::perform_kdf(*type_, &args, expected.len());
+
+            #[cfg(any(implementation = "custom", ossl3_supported = "argon2"))]
+            assert_eq!(key_out.unwrap(), expected);
+            #[cfg(not(any(implementation = "custom", ossl3_supported = "argon2")))]
+            assert!(matches!(key_out, Err(KdfError::Unimplemented(_))));
+        }
+    }
+
+    // SSHKDF test vector from the NIST CAVS SSH KDF tests, as used by OpenSSL
+    const SSHKDF_K: &str = "0000008055bae931c07fd824bf10add1902b6fbc7c665347383498a686929ff5a\
+                            25f8e40cb6645ea814fb1a5e0a11f852f86255641e5ed986e83a78bc8269480ea\
+                            c0b0dfd770cab92e7a28dd87ff452466d6ae867cead63b366b1c286e6c4811a9f\
+                            14c27aea14c5171d49b78c06e3735d36e6a3be321dd5fc82308f34ee1cb17fba94a59";
+    const SSHKDF_H: &str = "a4ebd45934f56792b5112dcd75a1075fdc889245";
+    const SSHKDF_VECTORS: &[(KdfSshType, &str)] = &[
+        (KdfSshType::InitialIvClientToServer, "e2f627c0b43f1ac1"),
+        (KdfSshType::InitialIvServerToClient, "58471445f342b181"),
+        (
+            KdfSshType::EncryptionKeyClientToServer,
+            "1ca9d310f86d51f6cb8e7007cb2b220d55c5281ce680b533",
+        ),
+        (
+            KdfSshType::EncryptionKeyServerToClient,
+            "2c60df8603d34cc1dbb03c11f725a44b44008851c73d6844",
+        ),
+        (
+            KdfSshType::IntegrityKeyClientToServer,
+            "472eb8a26166ae6aa8e06868e45c3b26e6eeed06",
+        ),
+        (
+            KdfSshType::IntegrityKeyServerToClient,
+            "e3e2fdb9d7bc21165a3dbe47e1eceb7764390bab",
+        ),
+    ];
+
+    #[test]
+    fn sshkdf_cavs() {
+        let key = hex::decode(SSHKDF_K).unwrap();
+        let xcghash = hex::decode(SSHKDF_H).unwrap();
+
+        for (type_, expected) in SSHKDF_VECTORS {
+            let expected = hex::decode(expected).unwrap();
+
+            let args = [
+                &KdfArgument::Digest(MessageDigest::sha1()),
+                &KdfArgument::Key(&key),
+                &KdfArgument::SshXcghash(&xcghash),
+                &KdfArgument::SshSessionId(&xcghash),
+                &KdfArgument::SshType(*type_),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::SshKdf, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+
+        let keys = crate::perform_sshkdf(
+            MessageDigest::sha1(),
+            &key,
+            &xcghash,
+            &xcghash,
+            Cipher::des_ede3_cbc(),
+            20,
+        )
+        .unwrap();
+        let derived = [
+            keys.iv_client_to_server,
+            keys.iv_server_to_client,
+            keys.encryption_key_client_to_server,
+            keys.encryption_key_server_to_client,
+            keys.integrity_key_client_to_server,
+            keys.integrity_key_server_to_client,
+        ];
+        for (derived, (_, expected)) in derived.iter().zip(SSHKDF_VECTORS) {
+            assert_eq!(derived, &hex::decode(expected).unwrap());
+        }
+    }
+
+    // The ACVP names for the digests of the SP800-56C auxiliary functions
+    fn acvp_to_md(value: &str) -> MessageDigest {
+        match value {
+            "SHA2-224" => MessageDigest::sha224(),
+            "SHA2-512" => MessageDigest::sha512(),
+            "SHA3-512" => MessageDigest::sha3_512(),
+            _ => panic!("Unsupported ACVP digest: {}", value),
+        }
+    }
+
+    #[test]
+    fn acvp_sskdf_one_step() {
+        let input_file =
+            std::path::PathBuf::from(&MANIFEST_DIR).join("test_assets/KDA_OneStep_sp800_56Cr2.txt");
+        let input_file = std::fs::File::open(input_file).unwrap();
+        let reader = std::io::BufReader::new(input_file).lines();
+
+        let mut num_executed = 0;
+        let mut aux_function: Option<String> = None;
+        let mut digest: Option<MessageDigest> = None;
+        let mut mac: Option<KdfMacType> = None;
+        let mut salt: Option<Vec<u8>> = None;
+        let mut z: Option<Vec<u8>> = None;
+        let mut fixed_info: Option<Vec<u8>> = None;
+
+        for line in reader {
+            let line = line.unwrap();
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = parse_kv(line);
+            let expected = match key {
+                "AUXFUNCTION" => {
+                    aux_function = Some(value.to_string());
+                    (digest, mac) = match value {
+                        "KMAC-128" => (None, Some(KdfMacType::Kmac128)),
+                        _ => match value.strip_prefix("HMAC-") {
+                            Some(md) => (None, Some(KdfMacType::Hmac(acvp_to_md(md)))),
+                            None => (Some(acvp_to_md(value)), None),
+                        },
+                    };
+                    continue;
+                }
+                "TGID" | "TCID" => {
+                    continue;
+                }
+                "Salt" => {
+                    salt = Some(hex::decode(value).unwrap());
+                    continue;
+                }
+                "Z" => {
+                    z = Some(hex::decode(value).unwrap());
+                    continue;
+                }
+                "FixedInfo" => {
+                    fixed_info = Some(hex::decode(value).unwrap());
+                    continue;
+                }
+                "DKM" => hex::decode(value).unwrap(),
+                _ => panic!("Unknown ACVP file key: {}", key),
+            };
+            num_executed += 1;
+
+            let z_arg = KdfArgument::Key(z.as_ref().unwrap());
+            let fixed_info_arg = KdfArgument::FixedInfo(fixed_info.as_ref().unwrap());
+            let salt_arg = KdfArgument::Salt(salt.as_ref().unwrap());
+            let digest_arg = digest.map(KdfArgument::Digest);
+            let mac_arg = mac.map(KdfArgument::Mac);
+
+            let mut args = vec![&z_arg, &fixed_info_arg];
+            // Without a salt, the default salt is used
+            if !salt.as_ref().unwrap().is_empty() {
+                args.push(&salt_arg);
+            }
+            args.extend(digest_arg.as_ref());
+            args.extend(mac_arg.as_ref());
+
+            let key_out = crate::perform_kdf(KdfType::SingleStep, &args, expected.len());
+
+            let is_kmac = matches!(mac, Some(KdfMacType::Kmac128));
+            if is_kmac
+                && cfg!(not(any(
+                    implementation = "ossl3",
+                    implementation = "custom"
+                )))
+            {
+                assert!(key_out.is_err());
+            } else {
+                assert_eq!(key_out.unwrap(), expected, "{:?}", aux_function);
+            }
+        }
+
+        assert_eq!(num_executed, 41);
+    }
+
+    // The ACVP sample vectors only cover KMAC128 with MacLen equal to the output length, these
+    // KMAC256 cases were generated with the OpenSSL 3 SSKDF
+    const SSKDF_Z: &str = "f7d98f0279a7854dc48afdee6bb53b185f9e1156a441fe923505c292350ded94";
+    const SSKDF_FIXED_INFO: &str = "8e2362ae07ed2762e63f2f189d328f37712fb66220e4abfb";
+    const SSKDF_SALT: &str = "10c73686ac2e302c228c7fbca35c01a8";
+
+    const SSKDF_KMAC256_VECTORS: &[(bool, usize, &str)] = &[
+        (
+            true,
+            64,
+            "9398e4ceadcba96cd75374fe88f5e133399dadc1355808fdb19f309535fed657\
+             191b594ddbd43ae991f2d979d1aedd57",
+        ),
+        (
+            false,
+            20,
+            "73b230e39175341b3dad6bf4a4deace9f660598f855f74a37fc523ae5fc8a1b3\
+             cdfe817560ce30014593939ec6ea7a042cbaadb5b1fcab551d5ee3286c79a3b4\
+             bd3ddbf8da25af2cf036814473637753",
+        ),
+    ];
+
+    #[test]
+    fn sskdf_kmac256_maclen() {
+        let z = hex::decode(SSKDF_Z).unwrap();
+        let fixed_info = hex::decode(SSKDF_FIXED_INFO).unwrap();
+        let salt = hex::decode(SSKDF_SALT).unwrap();
+
+        for (use_salt, maclen, output) in SSKDF_KMAC256_VECTORS {
+            let expected = hex::decode(output).unwrap();
+
+            let mac_arg = KdfArgument::Mac(KdfMacType::Kmac256);
+            let z_arg = KdfArgument::Key(&z);
+            let fixed_info_arg = KdfArgument::FixedInfo(&fixed_info);
+            let maclen_arg = KdfArgument::MacLen(*maclen);
+            let salt_arg = KdfArgument::Salt(&salt);
+
+            let mut args = vec![&mac_arg, &z_arg, &fixed_info_arg, &maclen_arg];
+            if *use_salt {
+                args.push(&salt_arg);
+            }
+
+            let key_out = crate::perform_kdf(KdfType::SingleStep, &args, expected.len());
+
+            #[cfg(any(implementation = "ossl3", implementation = "custom"))]
+            assert_eq!(key_out.unwrap(), expected);
+            #[cfg(not(any(implementation = "ossl3", implementation = "custom")))]
+            assert!(key_out.is_err());
+        }
+    }
+
+    #[test]
+    fn sskdf_kmac_maclen() {
+        let z = hex::decode(SSKDF_Z).unwrap();
+        let fixed_info = hex::decode(SSKDF_FIXED_INFO).unwrap();
+
+        for (maclen, length, valid) in [
+            (17, 17, true),
+            (20, 17, true),
+            (1, 17, false),
+            (0, 17, false),
+        ] {
+            let args = [
+                &KdfArgument::Mac(KdfMacType::Kmac128),
+                &KdfArgument::Key(&z),
+                &KdfArgument::FixedInfo(&fixed_info),
+                &KdfArgument::MacLen(maclen),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::SingleStep, &args, length);
+
+            if !valid {
+                assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+            } else if cfg!(any(implementation = "ossl3", implementation = "custom")) {
+                assert_eq!(key_out.unwrap().len(), length);
+            } else {
+                assert!(key_out.is_err());
+            }
+        }
+    }
+
+    // Test vectors from the CAVP SP800-135 ANSI X9.63 KDF tests
+    const X963_VECTORS: &[(&str, &str, &str, &str)] = &[
+        (
+            "SHA1",
+            "1c7d7b5f0597b03d06a018466ed1a93e30ed4b04dc64ccdd",
+            "",
+            "bf71dffd8f4d99223936beb46fee8ccc",
+        ),
+        (
+            "SHA256",
+            "96c05619d56c328ab95fe84b18264b08725b85e33fd34f08",
+            "",
+            "443024c3dae66b95e6f5670601558f71",
+        ),
+        (
+            "SHA256",
+            "22518b10e70f2a3f243810ae3254139efbee04aa57c7af7d",
+            "75eef81aa3041e33b80971203d2c0c52",
+            "c498af77161cc59f2962b9a713e2b215152d139766ce34a776df11866a69bf2e\
+             52a13d9c7c6fc878c50c5ea0bc7b00e0da2447cfd874f6cf92f30d0097111485\
+             500c90c3af8b487872d04685d14c8d1dc8d7fa08beb0ce0ababc11f0bd496269\
+             142d43525a78e5bc79a17f59676a5706dc54d54d4d1f0bd7e386128ec26afc21",
+        ),
+    ];
+
+    #[test]
+    fn x963_cavp() {
+        for (md, z, shared_info, key_data) in X963_VECTORS {
+            let z = hex::decode(z).unwrap();
+            let shared_info = hex::decode(shared_info).unwrap();
+            let expected = hex::decode(key_data).unwrap();
+
+            let args = [
+                &KdfArgument::Digest(hmac_to_md(md)),
+                &KdfArgument::Key(&z),
+                &KdfArgument::SharedInfo(&shared_info),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::X963, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    #[test]
+    fn x942_rfc2631() {
+        // RFC 2631 section 2.1.6, example 1
+        let zz = hex::decode("000102030405060708090a0b0c0d0e0f10111213").unwrap();
+        let expected = hex::decode("a09661392376f7044d9052a397883246b67f5f1ef63eb5fb").unwrap();
+
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha1()),
+            &KdfArgument::Key(&zz),
+            &KdfArgument::CekAlg(KdfCekAlg::Cipher(
+                Cipher::from_nid(Nid::ID_SMIME_ALG_CMS3DESWRAP).unwrap(),
+            )),
+        ];
+        let key_out = crate::perform_kdf(KdfType::X942Asn1, &args, expected.len()).unwrap();
+        assert_eq!(key_out, expected);
+
+        // No published vectors use the AES key wraps or the party infos, so the expected output
+        // is H(ZZ || OtherInfo) with the OtherInfo of RFC 2631 section 2.1.2 encoded by hand
+        let other_info = hex::decode(
+            "30323011060960864801650304012d040400000001a0060404a1a2a3a4a1070405b1b2b3b4b5\
+             a206040400000100a3040402c1c2",
+        )
+        .unwrap();
+        let expected =
+            openssl::hash::hash(MessageDigest::sha256(), &[&zz[..], &other_info].concat()).unwrap();
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha256()),
+            &KdfArgument::Key(&zz),
+            &KdfArgument::CekAlg(KdfCekAlg::Name("AES-256-WRAP")),
+            &KdfArgument::PartyUInfo(&[0xa1, 0xa2, 0xa3, 0xa4]),
+            &KdfArgument::PartyVInfo(&[0xb1, 0xb2, 0xb3, 0xb4, 0xb5]),
+            &KdfArgument::SuppPrivInfo(&[0xc1, 0xc2]),
+        ];
+        let key_out = crate::perform_kdf(KdfType::X942Asn1, &args, expected.len()).unwrap();
+        assert_eq!(key_out, &expected[..]);
+
+        let other_info =
+            hex::decode("301b30110609608648016503040105040400000001a206040400000100").unwrap();
+        let expected =
+            openssl::hash::hash(MessageDigest::sha512(), &[&zz[..], &other_info].concat()).unwrap();
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha512()),
+            &KdfArgument::Key(&zz),
+            &KdfArgument::CekAlg(KdfCekAlg::Name("id-aes128-wrap")),
+            &KdfArgument::UseKeyBits(false),
+            &KdfArgument::SuppPubInfo(&[0x00, 0x00, 0x01, 0x00]),
+        ];
+        let key_out = crate::perform_kdf(KdfType::X942Asn1, &args, 16).unwrap();
+        assert_eq!(key_out, &expected[..16]);
+
+        // The concatenation form is H(ZZ || counter || SharedInfo), the same as ANSI X9.63
+        for (md, z, shared_info, key_data) in X963_VECTORS {
+            let z = hex::decode(z).unwrap();
+            let shared_info = hex::decode(shared_info).unwrap();
+            let expected = hex::decode(key_data).unwrap();
+
+            let args = [
+                &KdfArgument::Digest(hmac_to_md(md)),
+                &KdfArgument::Key(&z),
+                &KdfArgument::SharedInfo(&shared_info),
+            ];
+            let key_out = crate::perform_kdf(KdfType::X942Concat, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha256()),
+            &KdfArgument::Key(&zz),
+            &KdfArgument::PartyUInfo(&[0xa1, 0xa2, 0xa3, 0xa4]),
+        ];
+        let key_out = crate::perform_kdf(KdfType::X942Concat, &args, 32);
+        assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+    }
+
+    #[test]
+    fn tls1_prf() {
+        // TLS 1.2 PRF with SHA-256
+        let secret = hex::decode("9bbe436ba940f017b17652849a71db35").unwrap();
+        let seed = hex::decode("a0ba9f936cda311827a6f796ffd5198c").unwrap();
+        let expected = hex::decode(
+            "e3f229ba727be17b8d122620557cd453c2aab21d07c3d495329b52d4e61edb5a\
+             6b301791e90d35c9c9a46b4e14baf9af0fa022f7077def17abfd3797c0564bab\
+             4fbc91666e9def9b97fce34f796789baa48082d122ee42c5a72e5a5110fff701\
+             87347b66",
+        )
+        .unwrap();
+
+        let args = [
+            &KdfArgument::Digest(MessageDigest::sha256()),
+            &KdfArgument::Secret(&secret),
+            &KdfArgument::TlsSeed(b"test label"),
+            &KdfArgument::TlsSeed(&seed),
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls1Prf, &args, expected.len()).unwrap();
+        assert_eq!(key_out, expected);
+
+        // TLS 1.1 master secret derivation with MD5-SHA1, from the OpenSSL test suite
+        let secret = hex::decode(
+            "bded7fa5c1699c010be23dd06ada3a48349f21e5f86263d512c0c5cc379f0e78\
+             0ec55d9844b2f1db02a96453513568d0",
+        )
+        .unwrap();
+        let client_random =
+            hex::decode("e5acaf549cd25c22d964c0d930fa4b5261d2507fad84c33715b7b9a864020693")
+                .unwrap();
+        let server_random =
+            hex::decode("135e4d557fdf3aa6406d82975d5c606a9734c9334b42136e96990fbd5358cdb2")
+                .unwrap();
+        let expected = hex::decode(
+            "2f6962dfbc744c4b2138bb6b3d33054c5ecc14f24851d9896395a44ab3964efc\
+             2090c5bf51a0891209f46c1e1e998f62",
+        )
+        .unwrap();
+
+        let args = [
+            &KdfArgument::Digest(MessageDigest::from_nid(Nid::MD5_SHA1).unwrap()),
+            &KdfArgument::Secret(&secret),
+            &KdfArgument::TlsSeed(b"master secret"),
+            &KdfArgument::TlsSeed(&client_random),
+            &KdfArgument::TlsSeed(&server_random),
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls1Prf, &args, expected.len()).unwrap();
+        assert_eq!(key_out, expected);
+    }
+
+    #[test]
+    fn tls13_rfc8448() {
+        // Key schedule of the simple 1-RTT handshake from RFC 8448 section 3
+        let digest_arg = KdfArgument::Digest(MessageDigest::sha256());
+        let prefix_arg = KdfArgument::Tls13Prefix(b"tls13 ");
+        let extract_arg = KdfArgument::HkdfMode(KdfHkdfMode::ExtractOnly);
+        let expand_arg = KdfArgument::HkdfMode(KdfHkdfMode::ExpandOnly);
+
+        let early_secret =
+            hex::decode("33ad0a1c607ec03b09e6cd9893680ce210adf300aa1f2660e1b22e10f170f92a")
+                .unwrap();
+        let args = [&extract_arg, &digest_arg];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 32).unwrap();
+        assert_eq!(key_out, early_secret);
+
+        let ecdhe = hex::decode("8bd4054fb55b9d63fdfbacf9f04b9f0d35e6d63f537563efd46272900f89492d")
+            .unwrap();
+        let handshake_secret =
+            hex::decode("1dc826e93606aa6fdc0aadc12f741b01046aa6b99f691ed221a9f0ca043fbeac")
+                .unwrap();
+        let args = [
+            &extract_arg,
+            &digest_arg,
+            &prefix_arg,
+            &KdfArgument::Tls13Label(b"derived"),
+            &KdfArgument::Key(&ecdhe),
+            &KdfArgument::Salt(&early_secret),
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 32).unwrap();
+        assert_eq!(key_out, handshake_secret);
+
+        let transcript_hash =
+            hex::decode("860c06edc07858ee8e78f0e7428c58edd6b43f2ca3e6e95f02ed063cf0e1cad8")
+                .unwrap();
+        let client_hs_traffic =
+            hex::decode("b3eddb126e067f35a780b3abf45e2d8f3b1a950738f52e9600746a0e27a55a21")
+                .unwrap();
+        let args = [
+            &expand_arg,
+            &digest_arg,
+            &prefix_arg,
+            &KdfArgument::Tls13Label(b"c hs traffic"),
+            &KdfArgument::Tls13Data(&transcript_hash),
+            &KdfArgument::Key(&handshake_secret),
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 32).unwrap();
+        assert_eq!(key_out, client_hs_traffic);
+
+        let key_arg = KdfArgument::Key(&client_hs_traffic);
+        let args = [
+            &expand_arg,
+            &digest_arg,
+            &prefix_arg,
+            &KdfArgument::Tls13Label(b"key"),
+            &key_arg,
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 16).unwrap();
+        assert_eq!(
+            key_out,
+            hex::decode("dbfaa693d1762c5b666af5d950258d01").unwrap()
+        );
+
+        let args = [
+            &expand_arg,
+            &digest_arg,
+            &prefix_arg,
+            &KdfArgument::Tls13Label(b"iv"),
+            &key_arg,
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 12).unwrap();
+        assert_eq!(key_out, hex::decode("5bd3c71b836e0b76bb73265f").unwrap());
+
+        // RFC 9147 section 5.9 only replaces the "tls13 " prefix with "dtls13", and has no
+        // vectors, so compare with HKDF-Expand of the HkdfLabel of RFC 8446 section 7.1
+        let hkdf_label = [&[0x00, 0x10, 0x09][..], b"dtls13key", &[0x00]].concat();
+        let args = [
+            &expand_arg,
+            &digest_arg,
+            &key_arg,
+            &KdfArgument::Info(&hkdf_label),
+        ];
+        let expected = crate::perform_kdf(KdfType::Hkdf, &args, 16).unwrap();
+        let args = [
+            &expand_arg,
+            &digest_arg,
+            &KdfArgument::Tls13Prefix(b"dtls13"),
+            &KdfArgument::Tls13Label(b"key"),
+            &key_arg,
+        ];
+        let key_out = crate::perform_kdf(KdfType::Tls13, &args, 16).unwrap();
+        assert_eq!(key_out, expected);
+    }
+
+    // DES3 vectors from RFC 3961 appendix A.3, AES vectors from the OpenSSL test suite
+    const KRB5_DK_VECTORS: &[(&str, &str, &str, &str)] = &[
+        (
+            "DES3",
+            "dce06b1f64c857a11c3db57c51899b2cc1791008ce973b92",
+            "0000000155",
+            "925179d04591a79b5d3192c4a7e9c289b049c71f6ee604cd",
+        ),
+        (
+            "DES3",
+            "5e13d31c70ef765746578531cb51c15bf11ca82c97cee9f2",
+            "00000001aa",
+            "9e58e5a146d9942a101c469845d67a20e3c4259ed913f207",
+        ),
+        (
+            "DES3",
+            "98e6fd8a04a4b6859b75a176540b9752bad3ecd610a252bc",
+            "0000000155",
+            "13fef80d763e94ec6d13fd2ca1d085070249dad39808eabf",
+        ),
+        (
+            "AES128",
+            "42263c6e89f4fc28b8df68ee09799f15",
+            "0000000299",
+            "34280a382bc92769b2da2f9ef066854b",
+        ),
+        (
+            "AES256",
+            "fe697b52bc0d3ce14432ba036a92e65bbb52280990a2fa27883998d72af30161",
+            "0000000299",
+            "bfab388bdcb238e9f9c98d6a878304f04d30c82556375ac507a7a852790f4674",
+        ),
+    ];
+
+    #[test]
+    fn krb5kdf_rfc3961() {
+        for (cipher, key, constant, expected) in KRB5_DK_VECTORS {
+            let cipher = match *cipher {
+                "DES3" => Cipher::des_ede3_cbc(),
+                "AES128" => Cipher::aes_128_cbc(),
+                "AES256" => Cipher::aes_256_cbc(),
+                _ => unreachable!(),
+            };
+            let key = hex::decode(key).unwrap();
+            let constant = hex::decode(constant).unwrap();
+            let expected = hex::decode(expected).unwrap();
+
+            let args = [
+                &KdfArgument::Cipher(cipher),
+                &KdfArgument::Key(&key),
+                &KdfArgument::Krb5Constant(&constant),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::Krb5, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    // SHA-1 vectors from the Bouncy Castle PKCS12Test, SHA-2 vectors with a non-ASCII password
+    // from the tests of the RustCrypto pkcs12 crate (0.1.0, tests/kdf.rs)
+    const PKCS12_VECTORS: &[(&str, &str, &str, KdfPkcs12Id, u64, &str)] = &[
+        (
+            "SHA1",
+            "smeg",
+            "0a58cf64530d823f",
+            KdfPkcs12Id::Key,
+            1,
+            "8aaae6297b6cb04642ab5b077851284eb7128f1a2a7fbca3",
+        ),
+        (
+            "SHA1",
+            "smeg",
+            "0a58cf64530d823f",
+            KdfPkcs12Id::Iv,
+            1,
+            "79993dfe048d3b76",
+        ),
+        (
+            "SHA1",
+            "smeg",
+            "3d83c0e4546ac140",
+            KdfPkcs12Id::Mac,
+            1,
+            "8d967d88f6caa9d714800ab3d48051d63f73a312",
+        ),
+        (
+            "SHA1",
+            "queeg",
+            "1682c0fc5b3f7ec5",
+            KdfPkcs12Id::Key,
+            1000,
+            "483dd6e919d7de2e8e648ba8f862f3fbfbdc2bcb2c02957f",
+        ),
+        (
+            "SHA1",
+            "queeg",
+            "1682c0fc5b3f7ec5",
+            KdfPkcs12Id::Iv,
+            1000,
+            "9d461d1b00355c50",
+        ),
+        (
+            "SHA256",
+            "ge@\u{e4}heim",
+            "0102030405060708",
+            KdfPkcs12Id::Key,
+            100,
+            "fae4d4957a3cc781e1180b9d4fb79c1e0c8579b746a3177e5b0768a3118bf863",
+        ),
+        (
+            "SHA256",
+            "ge@\u{e4}heim",
+            "0102030405060708",
+            KdfPkcs12Id::Iv,
+            100,
+            "e5ff813bc6547de5155b14d2fada85b3201a977349db6e26ccc998d9e8f83d6c",
+        ),
+        (
+            "SHA256",
+            "ge@\u{e4}heim",
+            "0102030405060708",
+            KdfPkcs12Id::Mac,
+            100,
+            "136355ed9434516682534f46d63956db5ff06b844702c2c1f3b46321e2524a4d",
+        ),
+        (
+            "SHA512",
+            "ge@\u{e4}heim",
+            "0102030405060708",
+            KdfPkcs12Id::Key,
+            100,
+            "b14a9f01bfd9dce4c9d66d2fe9937e5fd9f1afa59e370a6fa4fc81c1cc8ec8ee",
+        ),
+    ];
+
+    #[test]
+    fn pkcs12_kdf() {
+        for (md, password, salt, id, iterations, output) in PKCS12_VECTORS {
+            let salt = hex::decode(salt).unwrap();
+            let expected = hex::decode(output).unwrap();
+
+            let args = [
+                &KdfArgument::Digest(hmac_to_md(md)),
+                &KdfArgument::Password(password.as_bytes()),
+                &KdfArgument::Salt(&salt),
+                &KdfArgument::Pkcs12Id(*id),
+                &KdfArgument::Iterations(*iterations),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::Pkcs12, &args, expected.len()).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    // Key derivation test vectors from RFC 3711 appendix B.3
+    const SRTP_MASTER_KEY: &str = "e1f97a0d3e018be0d64fa32c06de4139";
+    const SRTP_MASTER_SALT: &str = "0ec675ad498afeebb6960b3aabe6";
+    const SRTP_VECTORS: &[(KdfSrtpLabel, &str)] = &[
+        (
+            KdfSrtpLabel::EncryptionKey,
+            "c61e7a93744f39ee10734afe3ff7a087",
+        ),
+        (KdfSrtpLabel::SaltingKey, "30cbbc08863d8c85d49db34a9ae1"),
+        (
+            KdfSrtpLabel::AuthenticationKey,
+            "cebe321f6ff7716b6fd4ab49af256a156d38baa4",
+        ),
+    ];
+
+    #[test]
+    fn srtp_rfc3711() {
+        let key = hex::decode(SRTP_MASTER_KEY).unwrap();
+        let salt = hex::decode(SRTP_MASTER_SALT).unwrap();
+
+        // With a rate of 2^16, every index below 2^16 derives the same keys as index zero
+        let kdr_index = [0x00, 0x00, 0x00, 0x00, 0xab, 0xcd];
+        let kdr_cases: [&[&KdfArgument]; 2] = [
+            &[],
+            &[
+                &KdfArgument::SrtpKdr(1 << 16),
+                &KdfArgument::SrtpIndex(&kdr_index),
+            ],
+        ];
+
+        for (label, expected) in SRTP_VECTORS {
+            let expected = hex::decode(expected).unwrap();
+
+            for kdr_args in kdr_cases {
+                let cipher_arg = KdfArgument::Cipher(Cipher::aes_128_ctr());
+                let key_arg = KdfArgument::Key(&key);
+                let salt_arg = KdfArgument::Salt(&salt);
+                let label_arg = KdfArgument::SrtpLabel(*label);
+                let mut args = vec![&cipher_arg, &key_arg, &salt_arg, &label_arg];
+                args.extend_from_slice(kdr_args);
+
+                let key_out = crate::perform_kdf(KdfType::Srtp, &args, expected.len());
+
+                #[cfg(any(implementation = "custom", ossl3_supported = "srtpkdf"))]
+                assert_eq!(key_out.unwrap(), expected);
+                #[cfg(not(any(implementation = "custom", ossl3_supported = "srtpkdf")))]
+                assert!(matches!(key_out, Err(KdfError::Unimplemented(_))));
+            }
+        }
+
+        let bad_kdr = [&KdfArgument::SrtpKdr(3)];
+        assert!(matches!(
+            crate::perform_kdf(KdfType::Srtp, &bad_kdr, 16),
+            Err(KdfError::InvalidOption(_))
+        ));
+    }
+
+    // RFC 3711 has no SRTCP vectors. These session keys authenticate and decrypt the
+    // libsrtp-generated AES_CM_128_HMAC_SHA1_80 SRTCP packets of the webrtc-srtp test suite.
+    const SRTCP_MASTER_KEY: &str = "fda62595d7f6926f7d9c024cc9209f34";
+    const SRTCP_MASTER_SALT: &str = "a9651985540b47be2f27a8b88123";
+    const SRTCP_VECTORS: &[(KdfSrtpLabel, &str)] = &[
+        (
+            KdfSrtpLabel::EncryptionKey,
+            "2588521d25900c4f266af56a2ab31a10",
+        ),
+        (KdfSrtpLabel::SaltingKey, "c40c68a81933a4f6db9eba2088ac"),
+        (
+            KdfSrtpLabel::AuthenticationKey,
+            "ea112bc396d996a9a77aa2542098390bb0a68c19",
+        ),
+    ];
+
+    #[test]
+    fn srtcp_libsrtp() {
+        let key = hex::decode(SRTCP_MASTER_KEY).unwrap();
+        let salt = hex::decode(SRTCP_MASTER_SALT).unwrap();
+
+        // The SRTCP index is 32 bits, the same rate argument as for SRTP applies
+        let kdr_index = [0x00, 0x00, 0xab, 0xcd];
+        let kdr_cases: [&[&KdfArgument]; 2] = [
+            &[],
+            &[
+                &KdfArgument::SrtpKdr(1 << 16),
+                &KdfArgument::SrtpIndex(&kdr_index),
+            ],
+        ];
+
+        for (label, expected) in SRTCP_VECTORS {
+            let expected = hex::decode(expected).unwrap();
+
+            for kdr_args in kdr_cases {
+                let cipher_arg = KdfArgument::Cipher(Cipher::aes_128_ctr());
+                let key_arg = KdfArgument::Key(&key);
+                let salt_arg = KdfArgument::Salt(&salt);
+                let label_arg = KdfArgument::SrtpLabel(*label);
+                let mut args = vec![&cipher_arg, &key_arg, &salt_arg, &label_arg];
+                args.extend_from_slice(kdr_args);
+
+                let key_out = crate::perform_kdf(KdfType::Srtcp, &args, expected.len());
+
+                #[cfg(any(implementation = "custom", ossl3_supported = "srtpkdf"))]
+                assert_eq!(key_out.unwrap(), expected);
+                #[cfg(not(any(implementation = "custom", ossl3_supported = "srtpkdf")))]
+                assert!(matches!(key_out, Err(KdfError::Unimplemented(_))));
+            }
+        }
+
+        // An SRTP sized index is rejected for SRTCP
+        let srtp_index = [0x00, 0x00, 0x00, 0x00, 0xab, 0xcd];
+        let bad_index = [&KdfArgument::SrtpIndex(&srtp_index)];
+        assert!(matches!(
+            crate::perform_kdf(KdfType::Srtcp, &bad_index, 16),
+            Err(KdfError::InvalidOption(_))
+        ));
+    }
+
+    // NIST CAVS 14.3 HMAC_DRBG vectors without reseeding, personalization string or additional
+    // input, as carried in the mbed TLS test suite (test_suite_hmac_drbg.no_reseed.data).
+    // The returned bits are those of the second generate call.
+    const HMAC_DRBG_CAVS_VECTORS: &[(&str, &str, &str, &str)] = &[
+        (
+            "SHA1",
+            "e91b63309e93d1d08e30e8d556906875",
+            "f59747c468b0d0da",
+            "b7928f9503a417110788f9d0c2585f8aee6fb73b220a626b3ab9825b7a9facc7\
+             9723d7e1ba9255e40e65c249b6082a7bc5e3f129d3d8f69b04ed1183419d6c4f\
+             2a13b304d2c5743f41c8b0ee73225347",
+        ),
+        (
+            "SHA1",
+            "d0c57f7dc0308115b1ea30e2ea2f7702",
+            "89cebdda617d132c",
+            "b797615a78d1afe74ebedb9d8948d82cf2bb586ed80146b96d41a709f689178b\
+             772dd342d29af5449694bf8eaf33a664a24c0ad29a12529eeaba478a799917ab\
+             4666de1b6eb2c7332017d67eea6fabd8",
+        ),
+        (
+            "SHA224",
+            "a76e77a969ab92645181f0157802523746c34bf321867641",
+            "051ed6ba39368033adc93d4e",
+            "8925987db5566e60520f09bdddab488292bed92cd385e5b6fc223e1919640b4e\
+             34e34575033e56c0a8f608be21d3d221c67d39abec98d81312f3a2653d55ffbf\
+             44c337c82bed314c211be23ec394399ba351c4687dce649e7c2a1ba7b0b5dab1\
+             25671b1bcf9008da65cad612d95ddc92",
+        ),
+        (
+            "SHA224",
+            "65cdaa5ab147d0c79fdd02b24fc94d0e427f59ef9a31f447",
+            "458c6befe0c2cde5a58c6b7d",
+            "0d164682b5bb552a53a2a942373639d98576450ca632faebc15060691a421946\
+             7c5aa106034cd19a214a0a4f31d402e68c4c565f49b33b680d522ef25f541e82\
+             02be779730376fdcf5b7b58fd6ac959204a88f91008651d2c02ada82505f914d\
+             4d9b9aea7967784e5320e185e1248270",
+        ),
+        (
+            "SHA256",
+            "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+            "659ba96c601dc69fc902940805ec0ca8",
+            "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+             d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+             07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+             961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8",
+        ),
+        (
+            "SHA256",
+            "79737479ba4e7642a221fcfd1b820b134e9e3540a35bb48ffae29c20f5418ea3",
+            "3593259c092bef4129bc2c6c9e19f343",
+            "cf5ad5984f9e43917aa9087380dac46e410ddc8a7731859c84e9d0f31bd43655\
+             b924159413e2293b17610f211e09f770f172b8fb693a35b85d3b9e5e63b1dc25\
+             2ac0e115002e9bedfb4b5b6fd43f33b8e0eafb2d072e1a6fee1f159df9b51e6c\
+             8da737e60d5032dd30544ec51558c6f080bdbdab1de8a939e961e06b5f1aca37",
+        ),
+        (
+            "SHA384",
+            "a1dc2dfeda4f3a1124e0e75ebfbe5f98cac11018221dda3fdcf8f9125d68447a",
+            "bae5ea27166540515268a493a96b5187",
+            "228293e59b1e4545a4ff9f232616fc5108a1128debd0f7c20ace837ca105cbf2\
+             4c0dac1f9847dafd0d0500721ffad3c684a992d110a549a264d14a8911c50be8\
+             cd6a7e8fac783ad95b24f64fd8cc4c8b649eac2b15b363e30df79541a6b8a1ca\
+             ac238949b46643694c85e1d5fcbcd9aaae6260acee660b8a79bea48e079ceb6a\
+             5eaf4993a82c3f1b758d7c53e3094eeac63dc255be6dcdcc2b51e5ca45d2b206\
+             84a5a8fa5806b96f8461ebf51bc515a7dd8c5475c0e70f2fd0faf7869a99ab6c",
+        ),
+        (
+            "SHA384",
+            "067fa0e25d71ea392671c24f38ef782ab3587a7b3c77ea756f7bd496b445b7a3",
+            "ce6acc722768ca0e03784b2217bc60e4",
+            "16eaa49510ffad8cc21ec32858640a0d6f34cb03e8649022aa5c3f566b44e8ac\
+             e7c3b056cf2a44b242de09ae21dba4275418933611875841b4f0944a8272848c\
+             5dc1aad685935e12511d5ee27e9162d4bb968afab53c4b338269c1c77da9d786\
+             17911ed4390cb20e88bf30b74fda66fe05df5537a759061d3ffd9231d811e8b3\
+             4213f22ab0b0ddafff7749a40243a901c310776e09d2e529806d4d6f06551789\
+             53c16707519c3c19b9aaa0d09fb676a9d23525c8bc388053bfccfbc368e3eb04",
+        ),
+        (
+            "SHA512",
+            "35049f389a33c0ecb1293238fd951f8ffd517dfde06041d32945b3e26914ba15",
+            "f7328760be6168e6aa9fb54784989a11",
+            "e76491b0260aacfded01ad39fbf1a66a88284caa5123368a2ad9330ee48335e3\
+             c9c9ba90e6cbc9429962d60c1a6661edcfaa31d972b8264b9d4562cf18494128\
+             a092c17a8da6f3113e8a7edfcd4427082bd390675e9662408144971717303d8d\
+             c352c9e8b95e7f35fa2ac9f549b292bc7c4bc7f01ee0a577859ef6e82d79ef23\
+             892d167c140d22aac32b64ccdfeee2730528a38763b24227f91ac3ffe47fb115\
+             38e435307e77481802b0f613f370ffb0dbeab774fe1efbb1a80d01154a9459e7\
+             3ad361108bbc86b0914f095136cbe634555ce0bb263618dc5c367291ce082551\
+             8987154fe9ecb052b3f0a256fcc30cc14572531c9628973639beda456f2bddf6",
+        ),
+        (
+            "SHA512",
+            "4cc8214cd7e85a76bfa735bbbfce926c0323fc348de6c05ed1800c2c8f58c6b1",
+            "001eb1f6b29b35242a3f8fa2e90003f4",
+            "1efa15d644e1bdf34eade3ff2f5e9ca45203ccaa1e534ac9b4287a846b71292b\
+             03102286d99f2be64b898fe909238f540ebc25f49522f60ef723a4c428ead530\
+             a97c62405cd5d9ecc54ac5baa47ac4f6195d637833f462d21a659b4903d9cfa6\
+             c9fd4512445f9abb5782899a6bb64592f3c2b3c745b18645301fdb09a6a331e9\
+             fb6d9654fc79c14ed83ac1684c755b9cb209885f86ff290a71f08a848b960152\
+             f05b1aa8566bd382ddd45521062831d7a0fb3a8bd8e112a91b5960690cd8585c\
+             1aa104514e3b9cbf52f6384e84c27bda2802fe9fb952cbf2bd607f869d0aeaa6\
+             b136c6a5f6e9b0522b6019b7ba6af6cff99fda612e024867decd8c0c6fde2034",
+        ),
+    ];
+
+    #[test]
+    fn hmac_drbg_cavs() {
+        for (md, entropy, nonce, output) in HMAC_DRBG_CAVS_VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            let nonce = hex::decode(nonce).unwrap();
+            let expected = hex::decode(output).unwrap();
+
+            let mut drbg =
+                crate::hmac_drbg::HmacDrbg::new(hmac_to_md(md), &entropy, &nonce).unwrap();
+            drbg.generate(expected.len()).unwrap();
+            assert_eq!(drbg.generate(expected.len()).unwrap(), expected);
+        }
+    }
+
+    // RFC 6979 appendix A.2.5: for P-256 with SHA-256, the first HMAC_DRBG output seeded with
+    // the private key and the message hash is already the nonce k
+    #[test]
+    fn hmac_drbg_kdf() {
+        for vector in &RFC6979_VECTORS[..2] {
+            let entropy = hex::decode(vector.private_key).unwrap();
+            let nonce = openssl::hash::hash(MessageDigest::sha256(), vector.message).unwrap();
+            let expected = hex::decode(vector.k).unwrap();
+
+            let args = [
+                &KdfArgument::Digest(hmac_to_md(vector.md)),
+                &KdfArgument::Entropy(&entropy),
+                &KdfArgument::Nonce(&nonce),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::HmacDrbg, &args, expected.len());
+
+            #[cfg(any(implementation = "custom", ossl3_supported = "hmac_drbg"))]
+            assert_eq!(key_out.unwrap(), expected);
+            #[cfg(not(any(implementation = "custom", ossl3_supported = "hmac_drbg")))]
+            assert!(matches!(key_out, Err(KdfError::Unimplemented(_))));
+        }
+    }
+
+    struct Rfc6979Vector {
+        order: &'static str,
+        private_key: &'static str,
+        md: &'static str,
+        message: &'static [u8],
+        k: &'static str,
+    }
+
+    // From RFC 6979 appendix A.2.5 (P-256) and A.2.8 (K-163), the latter needing retries
+    const RFC6979_VECTORS: &[Rfc6979Vector] = &[
+        Rfc6979Vector {
+            order: "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+            private_key: "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+            md: "SHA256",
+            message: b"sample",
+            k: "A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+        },
+        Rfc6979Vector {
+            order: "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+            private_key: "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+            md: "SHA256",
+            message: b"test",
+            k: "D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0",
+        },
+        Rfc6979Vector {
+            order: "4000000000000000000020108A2E0CC0D99F8A5EF",
+            private_key: "09A4D6792295A7F730FC3F2B49CBC0F62E862272F",
+            md: "SHA256",
+            message: b"sample",
+            k: "23AF4074C90A02B3FE61D286D5C87F425E6BDD81B",
+        },
+        Rfc6979Vector {
+            order: "4000000000000000000020108A2E0CC0D99F8A5EF",
+            private_key: "09A4D6792295A7F730FC3F2B49CBC0F62E862272F",
+            md: "SHA512",
+            message: b"test",
+            k: "331AD98D3186F73967B1E0B120C80B1E22EFC2988",
+        },
+    ];
+
+    #[test]
+    fn rfc6979_nonce() {
+        for vector in RFC6979_VECTORS {
+            let md = hmac_to_md(vector.md);
+            let order = BigNum::from_hex_str(vector.order).unwrap();
+            let private_key = BigNum::from_hex_str(vector.private_key).unwrap();
+            let hash = openssl::hash::hash(md, vector.message).unwrap();
+
+            let k = crate::rfc6979_nonce(md, &order, &private_key, &hash).unwrap();
+            assert_eq!(k, BigNum::from_hex_str(vector.k).unwrap());
+        }
+    }
+
+    const FEEDBACK_LABEL: &[u8] = b"kdf-label";
+    const FEEDBACK_CONTEXT: &[u8] = b"context";
+
+    // The CAVP-style file has no separate label and context, so check the default encoding
+    // K(i) = PRF(KI, K(i-1) || [i] || Label || 0x00 || Context || [L]) against the PRF directly
+    #[test]
+    fn kbkdf_feedback_mode() {
+        let key = hex::decode("202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f")
+            .unwrap();
+        let iv = hex::decode("a0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3b4b5b6b7b8b9babbbcbdbebf")
+            .unwrap();
+
+        let cases = [
+            (
+                KdfMacType::Hmac(MessageDigest::sha256()),
+                &key[..],
+                Some(&iv[..]),
+                80,
+            ),
+            (KdfMacType::Hmac(MessageDigest::sha1()), &key[..], None, 50),
+            (
+                KdfMacType::Cmac(Cipher::aes_128_cbc()),
+                &key[..16],
+                Some(&iv[..16]),
+                40,
+            ),
+            (KdfMacType::Cmac(Cipher::aes_256_cbc()), &key[..], None, 48),
+        ];
+
+        for (mac, key, iv, length) in cases {
+            let mut fixed_input = FEEDBACK_LABEL.to_vec();
+            fixed_input.push(0x00);
+            fixed_input.extend_from_slice(FEEDBACK_CONTEXT);
+            fixed_input.extend_from_slice(&(length as u32 * 8).to_be_bytes());
+
+            let mut expected = Vec::new();
+            let mut previous = iv.unwrap_or_default().to_vec();
+            let mut i: u32 = 1;
+            while expected.len() < length {
+                let input = [&previous[..], &i.to_be_bytes(), &fixed_input].concat();
+                previous = kbkdf_prf_reference(mac, key, &input);
+                expected.extend_from_slice(&previous);
+                i += 1;
+            }
+            expected.truncate(length);
+
+            let mode_arg = KdfArgument::KbMode(KdfKbMode::Feedback);
+            let mac_arg = KdfArgument::Mac(mac);
+            let key_arg = KdfArgument::Key(key);
+            let label_arg = KdfArgument::Salt(FEEDBACK_LABEL);
+            let context_arg = KdfArgument::KbInfo(FEEDBACK_CONTEXT);
+            let iv_arg = iv.map(KdfArgument::KbSeed);
+
+            let mut args = vec![&mode_arg, &mac_arg, &key_arg, &label_arg, &context_arg];
+            if let Some(iv_arg) = &iv_arg {
+                args.push(iv_arg);
+            }
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, length).unwrap();
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    // Without a counter, K(i) = PRF(KI, K(i-1) || FixedInput), checked against HMAC directly
+    #[cfg(implementation = "custom")]
+    #[test]
+    fn kbkdf_feedback_mode_without_counter() {
+        let key = [0x0b; 32];
+        let iv = [0x5a; 32];
+        let md = MessageDigest::sha256();
+
+        let mut fixed_input = FEEDBACK_LABEL.to_vec();
+        fixed_input.push(0x00);
+        fixed_input.extend_from_slice(FEEDBACK_CONTEXT);
+        fixed_input.extend_from_slice(&(48u32 * 8).to_be_bytes());
+
+        let mut expected = Vec::new();
+        let mut previous = iv.to_vec();
+        for _ in 0..2 {
+            let input = [&previous[..], &fixed_input].concat();
+            previous = kbkdf_prf_reference(KdfMacType::Hmac(md), &key, &input);
+            expected.extend_from_slice(&previous);
+        }
+        expected.truncate(48);
+
+        let args = [
+            &KdfArgument::KbMode(KdfKbMode::Feedback),
+            &KdfArgument::Mac(KdfMacType::Hmac(md)),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(FEEDBACK_LABEL),
+            &KdfArgument::KbInfo(FEEDBACK_CONTEXT),
+            &KdfArgument::KbSeed(&iv),
+            &KdfArgument::UseCounter(false),
+        ];
+
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 48).unwrap();
+        assert_eq!(key_out, expected);
+    }
+
+    #[test]
+    fn kbkdf_seed_and_before_iter_need_an_iteration_variable() {
+        let key = [0x0b; 32];
+        let iv = [0x5a; 32];
+        let mac = KdfArgument::Mac(KdfMacType::Hmac(MessageDigest::sha256()));
+        let key_arg = KdfArgument::Key(&key);
+        let seed = KdfArgument::KbSeed(&iv);
+        let before_iter = KdfArgument::CounterLocation(KdfCounterLocation::BeforeIter);
+        let counter = KdfArgument::KbMode(KdfKbMode::Counter);
+        let double_pipeline = KdfArgument::KbMode(KdfKbMode::DoublePipeline);
+
+        for args in [
+            vec![&mac, &key_arg, &seed],
+            vec![&mac, &key_arg, &double_pipeline, &seed],
+            vec![&mac, &key_arg, &before_iter],
+            vec![&mac, &key_arg, &counter, &before_iter],
+        ] {
+            assert!(matches!(
+                crate::perform_kdf(KdfType::KeyBased, &args, 32),
+                Err(KdfError::InvalidOption(_))
+            ));
+            #[cfg(implementation = "custom")]
+            assert!(matches!(
+                (crate::custom::IMPLEMENTATION.func)(KdfType::KeyBased, &args, 32),
+                Err(KdfError::InvalidOption(_))
+            ));
+        }
+    }
+
+    // The OpenSSL backends have no double-pipeline mode and must fall through cleanly,
+    // the custom implementation is covered by cavp_kbkdf_double_pipeline_mode
+    #[cfg(not(implementation = "custom"))]
+    #[test]
+    fn kbkdf_double_pipeline_mode() {
+        let key = [0x3c; 32];
+
+        let macs = [
+            KdfMacType::Hmac(MessageDigest::sha256()),
+            KdfMacType::Cmac(Cipher::aes_256_cbc()),
+        ];
+
+        for mac in macs {
+            for use_counter in [true, false] {
+                let args = [
+                    &KdfArgument::KbMode(KdfKbMode::DoublePipeline),
+                    &KdfArgument::Mac(mac),
+                    &KdfArgument::Key(&key),
+                    &KdfArgument::Salt(b"pipeline-label"),
+                    &KdfArgument::KbInfo(b"pipeline-context"),
+                    &KdfArgument::UseCounter(use_counter),
+                ];
+
+                let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 72);
+                assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn kbkdf_bit_length() {
+        let key = [0x2b; 32];
+        let label = b"bits-label";
+        let context = b"bits-context";
+        let md = MessageDigest::sha256();
+
+        let mode_arg = KdfArgument::KbMode(KdfKbMode::Counter);
+        let mac_arg = KdfArgument::Mac(KdfMacType::Hmac(md));
+        let key_arg = KdfArgument::Key(&key);
+        let label_arg = KdfArgument::Salt(label);
+        let context_arg = KdfArgument::KbInfo(context);
+        let args = [&mode_arg, &mac_arg, &key_arg, &label_arg, &context_arg];
+
+        // Whole bytes behave exactly as perform_kdf in every backend
+        assert_eq!(
+            crate::perform_kdf_bits(KdfType::KeyBased, &args, 256).unwrap(),
+            crate::perform_kdf(KdfType::KeyBased, &args, 32).unwrap()
+        );
+
+        assert!(matches!(
+            crate::perform_kdf_bits(KdfType::KeyBased, &args, 0),
+            Err(KdfError::InvalidOption(_))
+        ));
+
+        for length_bits in [1usize, 100, 260] {
+            let key_out = crate::perform_kdf_bits(KdfType::KeyBased, &args, length_bits);
+
+            #[cfg(implementation = "custom")]
+            {
+                // K(i) = HMAC(KI, [i]_32 || Label || 0x00 || Context || [L]_32)
+                let hmac_key = openssl::pkey::PKey::hmac(&key).unwrap();
+                let mut expected = Vec::new();
+                for i in 1..=length_bits.div_ceil(256) as u32 {
+                    let mut signer = openssl::sign::Signer::new(md, &hmac_key).unwrap();
+                    signer.update(&i.to_be_bytes()).unwrap();
+                    signer.update(label).unwrap();
+                    signer.update(&[0x00]).unwrap();
+                    signer.update(context).unwrap();
+                    signer.update(&(length_bits as u32).to_be_bytes()).unwrap();
+                    expected.extend_from_slice(&signer.sign_to_vec().unwrap());
+                }
+                expected.truncate(length_bits.div_ceil(8));
+                *expected.last_mut().unwrap() &= 0xff << (8 - length_bits % 8);
+
+                assert_eq!(key_out.unwrap(), expected);
+            }
+            #[cfg(not(implementation = "custom"))]
+            assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+        }
+    }
+
+    #[test]
+    fn kbkdf_split() {
+        let key = [0x4d; 32];
+        let args = [
+            &KdfArgument::KbMode(KdfKbMode::Counter),
+            &KdfArgument::Mac(KdfMacType::Hmac(MessageDigest::sha256())),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(b"split-label"),
+            &KdfArgument::KbInfo(b"split-context"),
+        ];
+
+        let keys = crate::perform_kdf_split(KdfType::KeyBased, &args, &[32, 0, 20, 12]).unwrap();
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 64).unwrap();
+
+        assert_eq!(keys.len(), 4);
+        assert_eq!(keys[0], &key_out[..32]);
+        assert!(keys[1].is_empty());
+        assert_eq!(keys[2], &key_out[32..52]);
+        assert_eq!(keys[3], &key_out[52..]);
+
+        assert!(matches!(
+            crate::perform_kdf_split(KdfType::KeyBased, &args, &[usize::MAX, 1]),
+            Err(KdfError::InvalidOption(_))
+        ));
+        assert!(matches!(
+            crate::perform_kdf_split(KdfType::KeyBased, &args, &[]),
+            Err(KdfError::InvalidOption(_))
+        ));
+        assert!(matches!(
+            crate::perform_kdf_split(KdfType::KeyBased, &args, &[0, 0]),
+            Err(KdfError::InvalidOption(_))
+        ));
+    }
+
+    #[test]
+    fn kbkdf_output_limits() {
+        let key = [0x6e; 32];
+        let mode_arg = KdfArgument::KbMode(KdfKbMode::Counter);
+        let mac_arg = KdfArgument::Mac(KdfMacType::Hmac(MessageDigest::sha256()));
+        let key_arg = KdfArgument::Key(&key);
+        let label_arg = KdfArgument::Salt(b"limit-label");
+
+        // At most 2^r - 1 blocks of 256 bits
+        for r in [8u8, 16] {
+            let max_len = ((1usize << r) - 1) * 32;
+            let r_arg = KdfArgument::R(r);
+            let args = [&mode_arg, &mac_arg, &key_arg, &label_arg, &r_arg];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, max_len);
+            #[cfg(any(implementation = "custom", ossl3_supported = "kbkdf_r"))]
+            assert_eq!(key_out.unwrap().len(), max_len);
+            #[cfg(not(any(implementation = "custom", ossl3_supported = "kbkdf_r")))]
+            assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, max_len + 1);
+            assert!(matches!(
+                key_out,
+                Err(KdfError::OutputTooLong(requested, allowed))
+                    if requested == (max_len as u64 + 1) * 8 && allowed == max_len as u64 * 8
+            ));
+        }
+
+        // L is 256 bits for 32 bytes of output, which does not fit in 8 bits
+        let lbits_arg = KdfArgument::LBits(8);
+        let args = [&mode_arg, &mac_arg, &key_arg, &label_arg, &lbits_arg];
+
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 31);
+        #[cfg(implementation = "custom")]
+        assert_eq!(key_out.unwrap().len(), 31);
+        #[cfg(not(implementation = "custom"))]
+        assert!(matches!(key_out, Err(KdfError::UnsupportedOption(_))));
+
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 32);
+        assert!(matches!(key_out, Err(KdfError::LengthNotEncodable(256, 8))));
+
+        // Widths other than whole bytes up to 32 bits are rejected by every backend
+        let invalid_widths = [
+            KdfArgument::R(12),
+            KdfArgument::R(72),
+            KdfArgument::LBits(72),
+        ];
+        for width_arg in &invalid_widths {
+            let args = [&mode_arg, &mac_arg, &key_arg, &label_arg, width_arg];
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 32);
+            assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+        }
+    }
+
+    #[test]
+    fn kbkdf_builder() {
+        let key = [0x71; 32];
+        let iv = [0x17; 32];
+        let mac = KdfMacType::Hmac(MessageDigest::sha256());
+
+        let kbkdf = crate::Kbkdf::builder(mac, &key)
+            .label(b"builder-label")
+            .context(b"builder-context")
+            .build()
+            .unwrap();
+        let args = [
+            &KdfArgument::KbMode(KdfKbMode::Counter),
+            &KdfArgument::Mac(mac),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(b"builder-label"),
+            &KdfArgument::KbInfo(b"builder-context"),
+        ];
+        let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 48).unwrap();
+
+        assert_eq!(kbkdf.derive(48).unwrap(), key_out);
+        assert_eq!(
+            kbkdf.derive_split(&[16, 32]).unwrap(),
+            [&key_out[..16], &key_out[16..]]
+        );
+
+        let kbkdf = crate::Kbkdf::builder(mac, &key)
+            .mode(KdfKbMode::Feedback)
+            .label(b"builder-label")
+            .seed(&iv)
+            .build()
+            .unwrap();
+        let args = [
+            &KdfArgument::KbMode(KdfKbMode::Feedback),
+            &KdfArgument::Mac(mac),
+            &KdfArgument::Key(&key),
+            &KdfArgument::Salt(b"builder-label"),
+            &KdfArgument::KbSeed(&iv),
+        ];
+        assert_eq!(
+            kbkdf.derive(40).unwrap(),
+            crate::perform_kdf(KdfType::KeyBased, &args, 40).unwrap()
+        );
+
+        let invalid = [
+            crate::Kbkdf::builder(mac, &key).seed(&iv),
+            crate::Kbkdf::builder(mac, &key).use_counter(false),
+            crate::Kbkdf::builder(mac, &key).r(12),
+            crate::Kbkdf::builder(mac, &key).use_l(false).l_bits(16),
+            crate::Kbkdf::builder(KdfMacType::Kmac128, &key).r(8),
+            crate::Kbkdf::builder(KdfMacType::Kmac256, &key).mode(KdfKbMode::Feedback),
+            crate::Kbkdf::builder(mac, &key).counter_location(KdfCounterLocation::BeforeIter),
+        ];
+        for builder in invalid {
+            assert!(matches!(builder.build(), Err(KdfError::InvalidOption(_))));
+        }
+    }
+
+    fn kbkdf_prf_reference(mac: KdfMacType, key: &[u8], input: &[u8]) -> Vec<u8> {
+        let pkey = match mac {
+            KdfMacType::Hmac(_) => openssl::pkey::PKey::hmac(key).unwrap(),
+            KdfMacType::Cmac(cipher) => openssl::pkey::PKey::cmac(&cipher, key).unwrap(),
+            _ => unreachable!(),
+        };
+        let mut signer = match mac {
+            KdfMacType::Hmac(md) => openssl::sign::Signer::new(md, &pkey).unwrap(),
+            _ => openssl::sign::Signer::new_without_digest(&pkey).unwrap(),
+        };
+        signer.update(input).unwrap();
+        signer.sign_to_vec().unwrap()
+    }
+
+    // Counter mode with the default encoding, PRF(KI, [i] || Label || 0x00 || Context || [L]) as in
+    // SP800-108 section 4.1, computed directly with the PRF
+    fn kbkdf_counter_reference(
+        mac: KdfMacType,
+        key: &[u8],
+        label: &[u8],
+        context: &[u8],
+        length: usize,
+    ) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut i: u32 = 1;
+        while output.len() < length {
+            let mut input = i.to_be_bytes().to_vec();
+            input.extend_from_slice(label);
+            input.push(0x00);
+            input.extend_from_slice(context);
+            input.extend_from_slice(&(length as u32 * 8).to_be_bytes());
+            output.extend_from_slice(&kbkdf_prf_reference(mac, key, &input));
+            i += 1;
+        }
+        output.truncate(length);
+        output
+    }
+
+    // There are no published KBKDF vectors for these ciphers, so compare with CMAC directly
+    #[test]
+    fn kbkdf_cmac_block_ciphers() {
+        let key = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let label = b"kdf-label";
+        let context = b"context";
+
+        // NID_aria_128_cbc, which the openssl crate has no constant for
+        let ciphers = [
+            Cipher::from_nid(Nid::from_raw(1066)).unwrap(),
+            Cipher::sm4_cbc(),
+        ];
+
+        for cipher in ciphers {
+            let args = [
+                &KdfArgument::KbMode(KdfKbMode::Counter),
+                &KdfArgument::Mac(KdfMacType::Cmac(cipher)),
+                &KdfArgument::Key(&key),
+                &KdfArgument::Salt(label),
+                &KdfArgument::KbInfo(context),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 32).unwrap();
+
+            let expected =
+                kbkdf_counter_reference(KdfMacType::Cmac(cipher), &key, label, context, 32);
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    // No published KBKDF vectors use these digests, so compare with HMAC directly
+    #[test]
+    fn kbkdf_hmac_digests() {
+        let key = [0x55; 32];
+        let label = b"kdf-label";
+        let context = b"context";
+
+        let digests = [
+            MessageDigest::sha3_256(),
+            MessageDigest::sha3_512(),
+            MessageDigest::from_name("SHA512-256").unwrap(),
+            MessageDigest::sm3(),
+        ];
+
+        for md in digests {
+            let args = [
+                &KdfArgument::KbMode(KdfKbMode::Counter),
+                &KdfArgument::Mac(KdfMacType::Hmac(md)),
+                &KdfArgument::Key(&key),
+                &KdfArgument::Salt(label),
+                &KdfArgument::KbInfo(context),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 48).unwrap();
+
+            let expected = kbkdf_counter_reference(KdfMacType::Hmac(md), &key, label, context, 48);
+            assert_eq!(key_out, expected);
+        }
+    }
+
+    // Extendable output functions have no fixed output length to use as PRF output
+    #[test]
+    fn kbkdf_hmac_xof() {
+        let key = [0x3a; 32];
+
+        for md in [MessageDigest::shake_128(), MessageDigest::shake_256()] {
+            let args = [
+                &KdfArgument::KbMode(KdfKbMode::Counter),
+                &KdfArgument::Mac(KdfMacType::Hmac(md)),
+                &KdfArgument::Key(&key),
+                &KdfArgument::Salt(b"kdf-label"),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 32);
+            assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+        }
+    }
+
+    // CMAC needs a block cipher, stream ciphers have no block to use as PRF output
+    #[test]
+    fn kbkdf_cmac_stream_ciphers() {
+        let key = [0x3a; 32];
+
+        for cipher in [Cipher::aes_128_ctr(), Cipher::chacha20()] {
+            let args = [
+                &KdfArgument::KbMode(KdfKbMode::Counter),
+                &KdfArgument::Mac(KdfMacType::Cmac(cipher)),
+                &KdfArgument::Key(&key[..cipher.key_len()]),
+                &KdfArgument::Salt(b"kdf-label"),
+            ];
+
+            let key_out = crate::perform_kdf(KdfType::KeyBased, &args, 32);
+            assert!(matches!(key_out, Err(KdfError::InvalidOption(_))));
+        }
+    }
+
     // Tests from OpenSSL 1.1
-    #[cfg(any(implementation = "ossl11", implementation = "ossl3"))]
     #[test]
     fn test_kdf_kbkdf_6803_128() {
         let input_key: [u8; 16] = [